@@ -0,0 +1,155 @@
+//! Balances of EOAs that are linked to a FIL account.
+//!
+//! Such an account owns two balances: the EVM-side balance kept by the
+//! bridge and the balance of its f1 actor. Reads report the sum of both.
+//!
+//! The bridge cannot pull FIL out of an f1 actor, so value leaving an EOA
+//! is always debited in this order:
+//!
+//! 1. FIL attached to the `ProcessTransaction` message by the linked f1
+//!    account, which is moved to the EVM side before the transaction runs;
+//! 2. the EVM-side balance.
+//!
+//! A transfer that exceeds the EVM-side balance fails, and the error says
+//! how much FIL has to be attached to cover the difference.
+
+use {
+  crate::state,
+  anyhow::anyhow,
+  fil_actors_runtime::runtime::Runtime,
+  fvm_evm::{token_to_u256, EthereumAccount, SignedTransaction, H160, U256},
+  fvm_ipld_blockstore::Blockstore,
+  fvm_shared::bigint::Zero,
+};
+
+/// Returns the balance held by the FIL account linked to `account`.
+///
+/// Zero for contracts and EOAs that are not linked yet.
+pub fn fil_balance<BS, RT>(rt: &RT, account: &EthereumAccount) -> anyhow::Result<U256>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  let actor_id = match account
+    .linked_fil_account()
+    .and_then(|address| rt.resolve_address(&address))
+    .and_then(|address| address.id().ok())
+  {
+    Some(id) => id,
+    None => return Ok(U256::zero()), // f1 actor not created on chain yet
+  };
+
+  match fvm_sdk::actor::balance_of(actor_id) {
+    Some(balance) => {
+      token_to_u256(&balance).ok_or_else(|| anyhow!("invalid FIL balance {balance}"))
+    }
+    None => Ok(U256::zero()),
+  }
+}
+
+/// Returns `account` with its balance being the sum of the EVM-side balance
+/// and the balance of its linked FIL account.
+pub fn unified_account<BS, RT>(
+  rt: &RT,
+  mut account: EthereumAccount,
+) -> anyhow::Result<EthereumAccount>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  account.balance = account
+    .balance
+    .checked_add(fil_balance(rt, &account)?)
+    .ok_or_else(|| anyhow!("account balance overflow"))?;
+  Ok(account)
+}
+
+/// Moves FIL attached to a transaction message to the EVM-side balance
+/// of the transaction sender.
+///
+/// Only the FIL account linked to the sender may attach value, otherwise
+/// it would be credited to an account its owner does not control.
+pub fn credit_attached_value<BS, RT>(
+  rt: &mut RT,
+  tx: &SignedTransaction,
+) -> anyhow::Result<()>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  let value = rt.message().value_received();
+  if value.is_zero() {
+    return Ok(());
+  }
+
+  let value =
+    token_to_u256(&value).ok_or_else(|| anyhow!("invalid attached value {value}"))?;
+
+  let sender = tx.sender_address()?;
+  let mut bridge_state = state::BridgeState::load(rt)?;
+  let mut bridge_accounts_map = bridge_state.accounts(rt)?;
+  let mut account = bridge_accounts_map
+    .get(&sender)?
+    .cloned()
+    .unwrap_or_default();
+
+  let caller = rt.message().caller();
+  let linked = account
+    .linked_fil_account()
+    .and_then(|address| rt.resolve_address(&address));
+  if linked != Some(caller) {
+    return Err(anyhow!(
+      "only the FIL account linked to {sender:?} can attach value to its transactions"
+    ));
+  }
+
+  credit(&mut account, value)?;
+  bridge_accounts_map.set(sender, account)?;
+  bridge_state.update_accounts(&mut bridge_accounts_map)?;
+
+  Ok(())
+}
+
+/// Adds `value` to the EVM-side balance of an account.
+pub fn credit(account: &mut EthereumAccount, value: U256) -> anyhow::Result<()> {
+  account.balance = account
+    .balance
+    .checked_add(value)
+    .ok_or_else(|| anyhow!("account balance overflow"))?;
+  Ok(())
+}
+
+/// Removes `value` from the EVM-side balance of an account.
+///
+/// Fails if the EVM-side balance is too low, even when the balance of the
+/// linked FIL account would cover the difference.
+pub fn debit<BS, RT>(
+  rt: &RT,
+  address: H160,
+  account: &mut EthereumAccount,
+  value: U256,
+) -> anyhow::Result<()>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  if let Some(balance) = account.balance.checked_sub(value) {
+    account.balance = balance;
+    return Ok(());
+  }
+
+  let shortfall = value - account.balance;
+  if fil_balance(rt, account)? >= shortfall {
+    if let Some(fil_account) = account.linked_fil_account() {
+      return Err(anyhow!(
+        "insufficient EVM balance for {address:?}: attach {shortfall} attoFIL from \
+         {fil_account} to the transaction"
+      ));
+    }
+  }
+
+  Err(anyhow!(
+    "insufficient funds for {address:?}: balance {}, required {value}",
+    unified_account(rt, *account)?.balance
+  ))
+}
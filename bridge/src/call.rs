@@ -0,0 +1,218 @@
+use {
+  crate::{create::contract_address, state},
+  anyhow::anyhow,
+  fil_actors_runtime::runtime::Runtime,
+  fvm_evm::{
+    deposit_code,
+    execute,
+    intrinsic_gas,
+    AccountKind,
+    Bytecode,
+    CallKind,
+    CallParams,
+    CallReturn,
+    ExecutionState,
+    InvocationParams,
+    Message,
+    Output,
+    StatusCode,
+    System,
+    TransactionContext,
+    H160,
+    U256,
+  },
+  fvm_ipld_blockstore::Blockstore,
+  fvm_ipld_encoding::{from_slice, RawBytes},
+  fvm_ipld_hamt::Hamt,
+  fvm_shared::{address::Address, bigint::BigInt},
+};
+
+const RUNTIME_SIMULATE_CALL_METHOD_NUM: u64 = 6;
+
+/// Upper bound used for gas estimation when the caller does not
+/// provide a gas limit.
+const DEFAULT_GAS_CAP: u64 = 30_000_000;
+
+/// Executes an unsigned call against the current state and returns its
+/// outcome without persisting any storage, balance or nonce changes.
+///
+/// Calls to contracts are executed by the contract actor itself, because
+/// that is where the contract code and storage live. Creations are
+/// executed locally on top of an empty storage that is never flushed.
+///
+/// Neither runs in static mode, since writes are discarded anyway and
+/// constructors as well as state changing functions have to be simulated.
+pub fn simulate_call<BS, RT>(
+  rt: &mut RT,
+  params: &CallParams,
+) -> anyhow::Result<CallReturn>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  let revision = state::BridgeState::load(rt)?.revision();
+  let context =
+    TransactionContext::new(params.from, U256::zero(), U256::zero(), revision)?;
+  let message = Message {
+    kind: match params.to {
+      Some(_) => CallKind::Call,
+      None => CallKind::Create,
+    },
+    is_static: false,
+    depth: 0,
+    gas: params.gas as i64,
+    recipient: params.to.unwrap_or_else(H160::zero),
+    sender: params.from,
+    input_data: params.data.clone(),
+    value: params.value,
+  };
+
+  match params.to {
+    Some(to) => simulate_contract_call(rt, to, message, context),
+    None => simulate_create(rt, message, context),
+  }
+}
+
+fn simulate_contract_call<BS, RT>(
+  rt: &mut RT,
+  to: H160,
+  message: Message,
+  context: TransactionContext,
+) -> anyhow::Result<CallReturn>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  let bridge_state = state::BridgeState::load(rt)?;
+  let accounts = bridge_state.accounts(rt)?;
+
+  match accounts.get(&to)?.map(|a| a.kind) {
+    Some(AccountKind::Contract { fil_account }) => {
+      let output = rt.send(
+        fil_account,
+        RUNTIME_SIMULATE_CALL_METHOD_NUM,
+        RawBytes::serialize(InvocationParams {
+          message,
+          context,
+          access_list: Vec::new(),
+        })?,
+        BigInt::default(),
+      )?;
+      Ok(from_slice(&output)?)
+    }
+    // calls to accounts without code succeed immediately
+    // and do not consume any execution gas.
+    _ => Ok(CallReturn::new(
+      &message,
+      Ok(Output {
+        status_code: StatusCode::Success,
+        gas_left: message.gas,
+        gas_refund: 0,
+        output_data: Default::default(),
+        reverted: false,
+      }),
+    )),
+  }
+}
+
+fn simulate_create<BS, RT>(
+  rt: &mut RT,
+  mut message: Message,
+  context: TransactionContext,
+) -> anyhow::Result<CallReturn>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  // the constructor runs at the address a transaction sent with the
+  // current nonce of the sender would deploy to.
+  let nonce = state::BridgeState::load(rt)?
+    .account(rt, &message.sender)?
+    .nonce;
+  message.recipient = contract_address(message.sender, nonce);
+
+  // constructor writes land in this temporary storage, which is
+  // dropped at the end of the simulation without being persisted.
+  let state_cid = Hamt::<_, U256, U256>::new(rt.store()).flush()?;
+  let bridge_addr = Address::new_id(fvm_sdk::message::receiver());
+  let system =
+    System::with_context(state_cid, rt, bridge_addr, message.recipient, context, &[])?;
+
+  let mut exec_state = ExecutionState::new(&message);
+  let bytecode = Bytecode::new(&message.input_data).map_err(|e| anyhow!(e))?;
+  let output = execute(&bytecode, &mut exec_state, &system).and_then(|mut output| {
+    // storing the returned code is part of the cost of a creation
+    if output.status_code == StatusCode::Success && !output.reverted {
+      deposit_code(&output.output_data, &mut output.gas_left)?;
+    }
+    Ok(output)
+  });
+
+  Ok(CallReturn::new(&message, output))
+}
+
+/// Finds the smallest gas limit, including intrinsic gas, with which the
+/// call or creation described by `params` runs to completion.
+///
+/// The search executes the message repeatedly without committing any
+/// state. Using the gas consumed by a single run is not enough, because
+/// of the 63/64 rule nested calls may fail with a limit equal to the gas
+/// used while succeeding with a slightly higher one.
+pub fn estimate_gas<BS, RT>(rt: &mut RT, params: &CallParams) -> anyhow::Result<u64>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  let intrinsic = intrinsic_gas(&params.data, params.to.is_none());
+
+  // a zero gas limit asks for the default cap
+  let mut hi = match params.gas {
+    0 => DEFAULT_GAS_CAP,
+    gas => gas,
+  };
+  if intrinsic > hi {
+    return Err(anyhow!(
+      "intrinsic gas {intrinsic} exceeds the gas allowance ({hi})"
+    ));
+  }
+  let mut lo = intrinsic - 1;
+
+  let run = |rt: &mut RT, gas_limit: u64| -> anyhow::Result<CallReturn> {
+    simulate_call(rt, &CallParams {
+      gas: gas_limit - intrinsic,
+      ..params.clone()
+    })
+  };
+
+  // if the call does not succeed with the highest allowance
+  // then it will not succeed with any lower value.
+  let ret = run(rt, hi)?;
+  if !ret.is_success() {
+    return Err(match ret.status_code {
+      StatusCode::Revert => anyhow!(ret.error_message()),
+      status => anyhow!("gas required exceeds allowance ({hi}): {status}"),
+    });
+  }
+
+  // most calls succeed with the gas they used plus the 1/64 that is
+  // withheld from every nested call, try that before searching.
+  let optimistic = (intrinsic + ret.gas_used) * 64 / 63;
+  if optimistic < hi {
+    if run(rt, optimistic)?.is_success() {
+      hi = optimistic;
+    } else {
+      lo = optimistic;
+    }
+  }
+
+  while lo + 1 < hi {
+    let mid = lo + (hi - lo) / 2;
+    if run(rt, mid)?.is_success() {
+      hi = mid;
+    } else {
+      lo = mid;
+    }
+  }
+
+  Ok(hi)
+}
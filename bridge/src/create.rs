@@ -1,16 +1,22 @@
 use {
-  crate::state,
+  crate::{balance::debit, invoke::apply_selfdestruct, state},
   anyhow::anyhow,
   cid::Cid,
   fil_actors_runtime::{runtime::Runtime, ActorError},
   fvm_evm::{
+    deposit_code,
     execute,
+    validate_initcode,
     AccountKind,
     Bytecode,
+    CallReturn,
+    CreateReturn,
     EthereumAccount,
     EvmContractRuntimeConstructor,
     ExecutionState,
     Message,
+    Output,
+    Revision,
     SignedTransaction,
     StatusCode,
     System,
@@ -46,15 +52,24 @@ pub struct ExecReturn {
 
 /// Determine the address of the newly created contract
 fn compute_contract_address(tx: &SignedTransaction) -> Result<H160, ActorError> {
+  Ok(contract_address(tx.sender_address()?, tx.nonce()))
+}
+
+/// The address of the contract created by `sender` with `nonce`, shared by
+/// creation transactions and simulated creations.
+pub fn contract_address(sender: H160, nonce: u64) -> H160 {
   let mut rlp = RlpStream::new();
-  rlp.append(&tx.sender_address()?);
-  rlp.append(&tx.nonce());
-  Ok(H160::from_slice(&Keccak256::digest(rlp.as_raw())[12..]))
+  rlp.append(&sender);
+  rlp.append(&nonce);
+  H160::from_slice(&Keccak256::digest(rlp.as_raw())[12..])
 }
 
 /// This is invoked when a transaction is sent to the ZERO address
 /// and has an input bytes. If there is no input bytes then it means
 /// that it is a simple burn.
+///
+/// Returns a `CreateReturn` whether or not the constructor succeeds, so a
+/// failed creation keeps the nonce it consumed, like a failed call.
 pub fn create_contract<BS, RT>(
   rt: &mut RT,
   tx: SignedTransaction,
@@ -63,6 +78,41 @@ where
   BS: Blockstore,
   RT: Runtime<BS>,
 {
+  // compute the potential contract address if the
+  // constructor runs successfully to completion.
+  let contract_address = compute_contract_address(&tx)?;
+
+  // the nonce is already consumed, a failed creation undoes the rest
+  let snapshot = state::BridgeState::snapshot()?;
+
+  // load global bridge HAMT
+  let mut bridge_state = state::BridgeState::load(rt)?;
+  let mut bridge_accounts_map = bridge_state.accounts(rt)?;
+
+  // the initial balance of the newly created contract, includes any
+  // value that was sent to this address before it was deployed.
+  let endowment = match bridge_accounts_map.get(&contract_address)? {
+    None => tx.value(),
+    Some(existing) => counterfactual_balance(contract_address, existing)?
+      .checked_add(tx.value())
+      .ok_or_else(|| anyhow!("contract balance overflow"))?,
+  };
+
+  // the endowment moves before the constructor runs, so balance
+  // queries made by the constructor already observe it.
+  let sender = tx.sender_address()?;
+  let mut from = bridge_accounts_map
+    .get(&sender)?
+    .cloned()
+    .unwrap_or_default();
+  debit(rt, sender, &mut from, tx.value())?;
+  bridge_accounts_map.set(sender, from)?;
+  bridge_accounts_map.set(contract_address, EthereumAccount {
+    balance: endowment,
+    ..Default::default()
+  })?;
+  bridge_state.update_accounts(&mut bridge_accounts_map)?;
+
   // Create a temporary contract state that will be used to store
   // results of constructor execution, then assigned as the state
   // root of a new EVM actor
@@ -76,16 +126,34 @@ where
 
   // Create an instance of the platform abstraction layer with it's state
   // rooted at the temporary contract state.
-  let system = System::new(state_cid, rt, bridge_addr, H160::zero(), &tx)?;
+  let system = System::new(
+    state_cid,
+    rt,
+    bridge_addr,
+    contract_address,
+    &tx,
+    bridge_state.revision(),
+  )?;
 
-  // compute the potential contract address if the
-  // constructor runs successfully to completion.
-  let contract_address = compute_contract_address(&tx)?;
+  let intrinsic_gas = tx.intrinsic_gas();
+  let mut message: Message = tx.try_into()?;
 
-  // the initial balance of the newly created contract
-  let endowment = tx.value();
+  // ADDRESS, storage and logs of the constructor refer to the new contract
+  message.recipient = contract_address;
 
-  let message: Message = tx.try_into()?;
+  let revision = bridge_state.revision();
+
+  // reject oversized init code before running any of it (EIP-3860)
+  if let Err(status) = validate_initcode(&message.input_data) {
+    return failed_create(
+      &snapshot,
+      contract_address,
+      intrinsic_gas,
+      &message,
+      Err(status),
+      revision,
+    );
+  }
 
   // create new execution context around this transaction
   let mut exec_state = ExecutionState::new(&message);
@@ -94,79 +162,199 @@ where
   let bytecode = Bytecode::new(&message.input_data).map_err(|e| anyhow!(e))?;
 
   // invoke the contract constructor
-  let exec_status = execute(&bytecode, &mut exec_state, &system)
+  let mut exec_status = execute(&bytecode, &mut exec_state, &system)
     .map_err(|e| ActorError::unspecified(format!("EVM execution error: {e:?}")))?;
 
-  if !exec_status.reverted
-    && exec_status.status_code == StatusCode::Success
-    && !exec_status.output_data.is_empty()
-  {
-    // load global bridge HAMT
-    let mut bridge_state = state::BridgeState::load(rt)?;
-    let mut bridge_accounts_map = bridge_state.accounts(rt)?;
-
-    // todo: support counterfactual deployments.
-    if !bridge_accounts_map.contains_key(&contract_address)? {
-      // constructor ran to completion successfully and returned
-      // the resulting bytecode.
-      let bytecode = exec_status.output_data.clone();
-
-      // this data will be used to intantiate a new EVM actor
-      // instance. Use the state populated by the EVM constructor
-      // code and the returned resulting bytecode. Also keep
-      // a reference to the bridge address on every EVM actor.
-      let runtime_params = EvmContractRuntimeConstructor {
-        bytecode,
-        initial_state: system.flush_state()?,
-        registry: bridge_addr,
-        address: contract_address,
-      };
-
-      fvm_sdk::debug::log(format!(
-        "Bridge thinks that runtime Cid is {:?}",
-        bridge_state.runtime_cid()
-      ));
-
-      // Params to the builtin InitActor#Exec method
-      let init_actor_params = ExecParams {
-        code_cid: *bridge_state.runtime_cid(),
-        constructor_params: RawBytes::serialize(runtime_params)?,
-      };
-
-      let init_actor_params = RawBytes::serialize(init_actor_params)?;
-
-      // let the Init Actor create a new address
-      let init_output = rt.send(
-        *fil_actors_runtime::INIT_ACTOR_ADDR,
-        INIT_ACTOR_EXEC_METHOD_NUM,
-        init_actor_params,
-        BigInt::default(),
+  if !exec_status.reverted && exec_status.status_code == StatusCode::Success {
+    // the constructor destroyed the contract before it was ever deployed,
+    // so only the endowment is moved and no contract actor gets created.
+    if let Some(beneficiary) = system.selfdestruct_beneficiary() {
+      apply_selfdestruct(
+        rt,
+        &mut bridge_accounts_map,
+        contract_address,
+        beneficiary,
+        true,
       )?;
+      bridge_state.update_accounts(&mut bridge_accounts_map)?;
+      return Ok(RawBytes::serialize(create_return(
+        contract_address,
+        intrinsic_gas,
+        &message,
+        &exec_status,
+        &system,
+        revision,
+      ))?);
+    }
+  }
+
+  if exec_status.reverted || exec_status.status_code != StatusCode::Success {
+    return failed_create(
+      &snapshot,
+      contract_address,
+      intrinsic_gas,
+      &message,
+      Ok(exec_status),
+      revision,
+    );
+  }
 
-      // the init actor should return the address of the new contract
-      let init_output: ExecReturn = from_slice(&init_output)?;
+  // there is no actor to deploy without code
+  if exec_status.output_data.is_empty() {
+    return failed_create(
+      &snapshot,
+      contract_address,
+      intrinsic_gas,
+      &message,
+      Ok(Output {
+        status_code: StatusCode::Failure,
+        ..exec_status
+      }),
+      revision,
+    );
+  }
 
-      // store the EVM to FVM account mapping
-      bridge_accounts_map.set(contract_address, EthereumAccount {
-        nonce: 0,
-        balance: endowment,
-        kind: AccountKind::Contract {
-          fil_account: init_output.robust_address,
-        },
-      })?;
+  // constructor ran to completion successfully and returned
+  // the resulting bytecode.
+  let bytecode = exec_status.output_data.clone();
 
-      // save accoutns state updates
-      bridge_state.update_accounts(&mut bridge_accounts_map)?;
+  // pay for storing the returned code and make sure it is deployable
+  // before asking the Init actor to create the contract actor.
+  if let Err(status) = deposit_code(&bytecode, &mut exec_status.gas_left) {
+    return failed_create(
+      &snapshot,
+      contract_address,
+      intrinsic_gas,
+      &message,
+      Err(status),
+      revision,
+    );
+  }
 
-      // return newly created contract address
-      Ok(RawBytes::serialize(contract_address)?)
-    } else {
-      unimplemented!("Not implemented yet");
-    }
-  } else {
-    // todo: more precise error handling
-    Err(anyhow!(ActorError::illegal_argument(format!(
-      "EVM constructor failed: {exec_status:?}"
-    ))))
+  // this data will be used to intantiate a new EVM actor
+  // instance. Use the state populated by the EVM constructor
+  // code and the returned resulting bytecode. Also keep
+  // a reference to the bridge address on every EVM actor.
+  let runtime_params = EvmContractRuntimeConstructor {
+    bytecode,
+    initial_state: system.flush_state()?,
+    registry: bridge_addr,
+    address: contract_address,
+    revision,
+  };
+
+  fvm_sdk::debug::log(format!(
+    "Bridge thinks that runtime Cid is {:?}",
+    bridge_state.runtime_cid()
+  ));
+
+  // Params to the builtin InitActor#Exec method
+  let init_actor_params = ExecParams {
+    code_cid: *bridge_state.runtime_cid(),
+    constructor_params: RawBytes::serialize(runtime_params)?,
+  };
+
+  let init_actor_params = RawBytes::serialize(init_actor_params)?;
+
+  // let the Init Actor create a new address
+  let init_output = rt.send(
+    *fil_actors_runtime::INIT_ACTOR_ADDR,
+    INIT_ACTOR_EXEC_METHOD_NUM,
+    init_actor_params,
+    BigInt::default(),
+  )?;
+
+  // the init actor should return the address of the new contract
+  let init_output: ExecReturn = from_slice(&init_output)?;
+
+  // store the EVM to FVM account mapping, this replaces any
+  // value-only account that existed at this address before.
+  bridge_accounts_map.set(contract_address, EthereumAccount {
+    nonce: 0,
+    balance: endowment,
+    kind: AccountKind::Contract {
+      fil_account: init_output.robust_address,
+    },
+  })?;
+
+  // save accoutns state updates
+  bridge_state.update_accounts(&mut bridge_accounts_map)?;
+
+  // return newly created contract address along with the gas used
+  // by the whole transaction, which includes the code deposit.
+  Ok(RawBytes::serialize(create_return(
+    contract_address,
+    intrinsic_gas,
+    &message,
+    &exec_status,
+    &system,
+    revision,
+  ))?)
+}
+
+/// Gas accounting and logs of a successful creation, `output` being the
+/// result of the constructor after paying for the code deposit.
+fn create_return<BS: Blockstore>(
+  address: H160,
+  intrinsic_gas: u64,
+  message: &Message,
+  output: &Output,
+  system: &System<BS>,
+  revision: Revision,
+) -> CreateReturn {
+  let gas_used = intrinsic_gas + (message.gas - output.gas_left) as u64;
+  CreateReturn {
+    status_code: StatusCode::Success,
+    address,
+    gas_used,
+    gas_refund: revision.capped_refund(output.gas_refund, gas_used),
+    revert_data: Default::default(),
+    logs: system.logs(),
+  }
+}
+
+/// Undoes the account changes of a failed creation, except for the nonce
+/// it consumed, and reports the failure to the sender.
+fn failed_create(
+  snapshot: &Cid,
+  address: H160,
+  intrinsic_gas: u64,
+  message: &Message,
+  result: Result<Output, StatusCode>,
+  revision: Revision,
+) -> anyhow::Result<RawBytes> {
+  state::BridgeState::restore(snapshot)?;
+
+  let mut result = CallReturn::new(message, result);
+  result.charge_intrinsic_gas(intrinsic_gas, revision);
+  Ok(RawBytes::serialize(CreateReturn {
+    status_code: result.status_code,
+    address,
+    gas_used: result.gas_used,
+    gas_refund: 0,
+    revert_data: result.revert_data,
+    logs: Vec::new(),
+  })?)
+}
+
+/// Returns the balance that a contract deployed on top of an already
+/// registered address inherits.
+///
+/// Following Ethereum semantics, deployment is only allowed over
+/// addresses that have no code and a zero nonce, meaning they have
+/// never been used other than receiving value.
+fn counterfactual_balance(
+  address: H160,
+  existing: &EthereumAccount,
+) -> anyhow::Result<U256> {
+  match existing {
+    EthereumAccount {
+      nonce: 0,
+      balance,
+      kind: AccountKind::ExternallyOwned { .. },
+    } => Ok(*balance),
+    _ => Err(anyhow!(ActorError::illegal_argument(format!(
+      "contract address collision: {address:?} is already in use"
+    )))),
   }
 }
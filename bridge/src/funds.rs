@@ -0,0 +1,116 @@
+use {
+  crate::{
+    balance::{credit, debit},
+    nonce::use_nonce,
+    state,
+  },
+  anyhow::anyhow,
+  fil_actors_runtime::runtime::Runtime,
+  fvm_evm::{
+    id_to_h160,
+    token_to_u256,
+    u256_to_token,
+    SignedTransaction,
+    TransactionAction,
+    H160,
+    U256,
+  },
+  fvm_ipld_blockstore::Blockstore,
+  fvm_ipld_encoding::RawBytes,
+  fvm_shared::{address::Address, bigint::Zero, METHOD_SEND},
+  serde_tuple::{Deserialize_tuple, Serialize_tuple},
+};
+
+/// Parameters of the bridge `Withdraw` method.
+///
+/// `transaction` is an RLP encoded signed Ethereum transaction that
+/// authorizes the withdrawal. It must be a call to the
+/// [`withdrawal_address`] that transfers exactly `amount`, carries the bytes
+/// of `fil_address` as its input and uses the next nonce of the sender.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct WithdrawParams {
+  pub amount: U256,
+  pub fil_address: Address,
+  pub transaction: Vec<u8>,
+}
+
+/// Returns the EVM address withdrawal transactions are sent to, which is
+/// the address embedding the actor ID of the bridge itself.
+///
+/// `ProcessTransaction` refuses transactions sent to it, so a signed
+/// withdrawal can never be executed as a regular transfer.
+pub fn withdrawal_address<BS, RT>(rt: &RT) -> anyhow::Result<H160>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  Ok(id_to_h160(rt.message().receiver().id()?))
+}
+
+/// Credits the FIL attached to the message to the EVM-side balance
+/// of `address`.
+pub fn deposit<BS, RT>(rt: &mut RT, address: H160) -> anyhow::Result<()>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  let value = rt.message().value_received();
+  if value.is_zero() {
+    return Err(anyhow!("no FIL attached to the deposit"));
+  }
+  let value =
+    token_to_u256(&value).ok_or_else(|| anyhow!("invalid deposit amount {value}"))?;
+
+  let mut bridge_state = state::BridgeState::load(rt)?;
+  let mut bridge_accounts_map = bridge_state.accounts(rt)?;
+
+  let mut account = bridge_accounts_map
+    .get(&address)?
+    .cloned()
+    .unwrap_or_default();
+  credit(&mut account, value)?;
+  bridge_accounts_map.set(address, account)?;
+  bridge_state.update_accounts(&mut bridge_accounts_map)?;
+
+  Ok(())
+}
+
+/// Debits the EVM-side balance of the signer of the withdrawal transaction
+/// and sends the amount to a Filecoin address.
+pub fn withdraw<BS, RT>(rt: &mut RT, params: WithdrawParams) -> anyhow::Result<()>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  let tx = SignedTransaction::try_from(params.transaction.as_slice())
+    .map_err(|e| anyhow!("invalid withdrawal transaction: {e:?}"))?;
+
+  if tx.action() != TransactionAction::Call(withdrawal_address(rt)?)
+    || tx.value() != params.amount
+    || tx.input().as_ref() != params.fil_address.to_bytes().as_slice()
+  {
+    return Err(anyhow!("transaction does not authorize this withdrawal"));
+  }
+
+  // the nonce makes every signed withdrawal usable only once
+  let sender = use_nonce(rt, &tx)?;
+
+  let mut bridge_state = state::BridgeState::load(rt)?;
+  let mut bridge_accounts_map = bridge_state.accounts(rt)?;
+  let mut account = bridge_accounts_map
+    .get(&sender)?
+    .cloned()
+    .unwrap_or_default();
+  debit(rt, sender, &mut account, params.amount)?;
+  bridge_accounts_map.set(sender, account)?;
+  bridge_state.update_accounts(&mut bridge_accounts_map)?;
+
+  rt.send(
+    params.fil_address,
+    METHOD_SEND,
+    RawBytes::default(),
+    u256_to_token(params.amount),
+  )?;
+
+  Ok(())
+}
@@ -1,17 +1,184 @@
 use {
+  crate::{
+    balance::{credit, debit},
+    state,
+    transfer::transfer_tokens,
+  },
+  anyhow::anyhow,
   fil_actors_runtime::runtime::Runtime,
-  fvm_evm::SignedTransaction,
+  fvm_evm::{
+    h160_to_fil_address,
+    h160_to_id,
+    u256_to_token,
+    AccountKind,
+    EthereumAccount,
+    InvocationParams,
+    InvocationReturn,
+    Message,
+    SignedTransaction,
+    TransactionAction,
+    TransactionContext,
+    H160,
+  },
   fvm_ipld_blockstore::Blockstore,
-  fvm_ipld_encoding::RawBytes,
+  fvm_ipld_encoding::{from_slice, RawBytes},
+  fvm_ipld_hamt::Hamt,
+  fvm_shared::{bigint::BigInt, METHOD_SEND},
 };
 
+const RUNTIME_INVOKE_CONTRACT_METHOD_NUM: u64 = 2;
+const RUNTIME_DELETE_CONTRACT_METHOD_NUM: u64 = 7;
+
+/// This is invoked when a transaction is sent to a non-zero address.
+/// The message is executed by the EVM runtime actor of the contract and
+/// any account changes resulting from it are applied afterwards.
+///
+/// Returns an `InvocationReturn` whether or not execution succeeds, so a
+/// revert reaches the caller with its raw data and decoded reason.
 pub fn invoke_contract<BS, RT>(
-  _rt: &mut RT,
-  _tx: SignedTransaction,
+  rt: &mut RT,
+  tx: SignedTransaction,
 ) -> anyhow::Result<RawBytes>
 where
   BS: Blockstore,
   RT: Runtime<BS>,
 {
-  todo!()
+  let to = match tx.action() {
+    TransactionAction::Call(to) => to,
+    TransactionAction::Create => return Err(anyhow!("expected a call transaction")),
+  };
+
+  // native actors are addressed by their embedded ID, no lookup needed
+  if h160_to_id(&to).is_some() {
+    return transfer_tokens(rt, tx);
+  }
+
+  let snapshot = state::BridgeState::snapshot()?;
+  let mut bridge_state = state::BridgeState::load(rt)?;
+  let mut bridge_accounts_map = bridge_state.accounts(rt)?;
+
+  let fil_account = match bridge_accounts_map.get(&to)?.map(|a| a.kind) {
+    Some(AccountKind::Contract { fil_account }) => fil_account,
+    _ => return transfer_tokens(rt, tx), // plain value transfer to an EOA
+  };
+
+  // the contract observes the transferred value in its own balance,
+  // so it has to be moved before the contract runs.
+  if !tx.value().is_zero() {
+    let sender = tx.sender_address()?;
+    let mut from = bridge_accounts_map
+      .get(&sender)?
+      .cloned()
+      .unwrap_or_default();
+    debit(rt, sender, &mut from, tx.value())?;
+    bridge_accounts_map.set(sender, from)?;
+
+    let mut contract = bridge_accounts_map.get(&to)?.cloned().unwrap_or_default();
+    credit(&mut contract, tx.value())?;
+    bridge_accounts_map.set(to, contract)?;
+
+    bridge_state.update_accounts(&mut bridge_accounts_map)?;
+  }
+
+  let context = TransactionContext::for_transaction(&tx, bridge_state.revision())?;
+  let intrinsic_gas = tx.intrinsic_gas();
+  let access_list = tx.transaction.access_list().to_vec();
+  let message: Message = tx.try_into()?;
+
+  let output = rt.send(
+    fil_account,
+    RUNTIME_INVOKE_CONTRACT_METHOD_NUM,
+    RawBytes::serialize(InvocationParams {
+      message,
+      context,
+      access_list,
+    })?,
+    BigInt::default(),
+  )?;
+
+  let mut output: InvocationReturn = from_slice(&output)?;
+  output
+    .result
+    .charge_intrinsic_gas(intrinsic_gas, bridge_state.revision());
+  if !output.result.is_success() {
+    // the transaction still consumes its nonce, but the value it carried
+    // stays with the sender.
+    state::BridgeState::restore(&snapshot)?;
+    return Ok(RawBytes::serialize(output)?);
+  }
+
+  if let Some(beneficiary) = output.selfdestruct {
+    let delete = !bridge_state.revision().selfdestruct_same_tx_only();
+    apply_selfdestruct(rt, &mut bridge_accounts_map, to, beneficiary, delete)?;
+    bridge_state.update_accounts(&mut bridge_accounts_map)?;
+
+    // the contract actor goes away only once its balance has moved
+    if delete {
+      rt.send(
+        fil_account,
+        RUNTIME_DELETE_CONTRACT_METHOD_NUM,
+        RawBytes::default(),
+        BigInt::default(),
+      )?;
+    }
+  }
+
+  Ok(RawBytes::serialize(output)?)
+}
+
+/// Moves the balance of a self-destructed contract to the beneficiary,
+/// creating the beneficiary account if it does not exist yet.
+///
+/// A beneficiary that embeds an actor ID receives the balance as FIL, the
+/// same way value sent to such an address does in [`transfer_tokens`].
+///
+/// If `delete` is set the contract account is removed, and a balance sent
+/// to the contract itself is burned. Otherwise the account is kept
+/// and sending the balance to itself has no effect (EIP-6780).
+pub fn apply_selfdestruct<BS, RT>(
+  rt: &RT,
+  accounts: &mut Hamt<&BS, EthereumAccount, H160>,
+  contract: H160,
+  beneficiary: H160,
+  delete: bool,
+) -> anyhow::Result<()>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  if beneficiary == contract && !delete {
+    return Ok(());
+  }
+
+  let mut account = accounts.get(&contract)?.cloned().unwrap_or_default();
+  let balance = std::mem::take(&mut account.balance);
+
+  if beneficiary != contract {
+    match h160_to_fil_address(&beneficiary) {
+      // the bridge holds the FIL backing the contract balance
+      Some(actor) => {
+        if !balance.is_zero() {
+          rt.send(
+            actor,
+            METHOD_SEND,
+            RawBytes::default(),
+            u256_to_token(balance),
+          )?;
+        }
+      }
+      None => {
+        let mut recipient = accounts.get(&beneficiary)?.cloned().unwrap_or_default();
+        credit(&mut recipient, balance)?;
+        accounts.set(beneficiary, recipient)?;
+      }
+    }
+  }
+
+  if delete {
+    accounts.delete(&contract)?;
+  } else {
+    accounts.set(contract, account)?;
+  }
+
+  Ok(())
 }
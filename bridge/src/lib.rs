@@ -1,5 +1,6 @@
 use {
-  cid::Cid,
+  balance::{credit_attached_value, unified_account},
+  call::{estimate_gas, simulate_call},
   create::create_contract,
   fil_actors_runtime::{
     actor_error,
@@ -7,19 +8,33 @@ use {
     ActorError,
     INIT_ACTOR_ADDR,
   },
-  fvm_evm::TransactionAction,
+  funds::{deposit, withdraw, withdrawal_address, WithdrawParams},
+  fvm_evm::{
+    BridgeConstructorParams,
+    CallParams,
+    EthereumAccount,
+    TransactionAction,
+    H160,
+  },
   fvm_ipld_blockstore::Blockstore,
   fvm_ipld_encoding::{from_slice, RawBytes},
   fvm_sdk::debug,
   fvm_shared::{MethodNum, METHOD_CONSTRUCTOR},
   invoke::invoke_contract,
+  link::link_sender_account,
+  nonce::use_nonce,
   num_derive::FromPrimitive,
   num_traits::FromPrimitive,
   transfer::transfer_tokens,
 };
 
+mod balance;
+mod call;
 mod create;
+mod funds;
 mod invoke;
+mod link;
+mod nonce;
 mod state;
 mod transfer;
 
@@ -31,11 +46,21 @@ fil_actors_runtime::wasm_trampoline!(BridgeActor);
 pub enum Method {
   Constructor = METHOD_CONSTRUCTOR,
   ProcessTransaction = 2,
+  Call = 3,
+  EstimateGas = 4,
+  GetAccount = 5,
+  GetNonce = 6,
+  GetBalance = 7,
+  Deposit = 8,
+  Withdraw = 9,
 }
 
 pub struct BridgeActor;
 impl BridgeActor {
-  pub fn constructor<BS, RT>(rt: &mut RT, runtime_cid: Cid) -> Result<(), ActorError>
+  pub fn constructor<BS, RT>(
+    rt: &mut RT,
+    params: BridgeConstructorParams,
+  ) -> Result<(), ActorError>
   where
     BS: Blockstore,
     RT: Runtime<BS>,
@@ -44,7 +69,7 @@ impl BridgeActor {
 
     // Initialize the global state of the bridge to an empty map.
     // todo: in later iterations initialize with precompiles.
-    state::BridgeState::create(rt, &runtime_cid)
+    state::BridgeState::create(rt, &params.runtime_cid, params.revision, params.chain_id)
       .map_err(|e| ActorError::illegal_argument(format!("{e:?}")))?;
     Ok(())
   }
@@ -64,6 +89,21 @@ impl BridgeActor {
       .map_err(|e| ActorError::illegal_argument(format!("{e:?}")))?;
     debug::log(format!("FVM transaction: {transaction:#?}"));
 
+    let withdrawal =
+      withdrawal_address(rt).map_err(|e| ActorError::illegal_state(format!("{e:?}")))?;
+    if transaction.action() == TransactionAction::Call(withdrawal) {
+      return Err(ActorError::illegal_argument(
+        "withdrawals must be submitted through the Withdraw method".into(),
+      ));
+    }
+
+    // first transaction from an EOA reveals its FIL equivalent, and no
+    // value may move before the transaction is known not to be a replay.
+    link_sender_account(rt, &transaction)
+      .and_then(|_| use_nonce(rt, &transaction))
+      .and_then(|_| credit_attached_value(rt, &transaction))
+      .map_err(|e| ActorError::illegal_argument(format!("{e:?}")))?;
+
     match transaction.action() {
       TransactionAction::Call(_) => invoke_contract(rt, transaction),
       TransactionAction::Create => {
@@ -77,6 +117,122 @@ impl BridgeActor {
     }
     .map_err(|e| ActorError::unspecified(format!("EVM Error: {e:?}")))
   }
+
+  /// Executes an unsigned call against the current state without
+  /// persisting any changes, the equivalent of `eth_call`.
+  ///
+  /// The call is not executed in static mode. Storage writes, logs and
+  /// SELFDESTRUCT behave as they would in a transaction and are discarded
+  /// afterwards, so state changing functions and constructors can be
+  /// simulated too. A creation runs at the address the sender would deploy
+  /// to with its current nonce.
+  ///
+  /// Returns the output data, gas used and revert data of the execution.
+  pub fn call<BS, RT>(rt: &mut RT, params: CallParams) -> Result<RawBytes, ActorError>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    rt.validate_immediate_caller_accept_any()?;
+
+    let ret = simulate_call(rt, &params)
+      .map_err(|e| ActorError::unspecified(format!("EVM Error: {e:?}")))?;
+    RawBytes::serialize(ret).map_err(Into::into)
+  }
+
+  /// Estimates the gas limit a transaction needs to run to completion,
+  /// the equivalent of `eth_estimateGas`.
+  ///
+  /// Fails with the revert reason if the call cannot succeed with the
+  /// gas limit in `params`, or the block gas limit if that is zero. A
+  /// non-zero gas limit below the intrinsic gas of the call is an error.
+  pub fn estimate_gas<BS, RT>(
+    rt: &mut RT,
+    params: CallParams,
+  ) -> Result<RawBytes, ActorError>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    rt.validate_immediate_caller_accept_any()?;
+
+    let gas = estimate_gas(rt, &params)
+      .map_err(|e| ActorError::illegal_argument(format!("EVM Error: {e:?}")))?;
+    RawBytes::serialize(gas).map_err(Into::into)
+  }
+
+  /// Returns the `EthereumAccount` stored under an EVM address.
+  ///
+  /// The balance of EOAs includes the balance of their linked FIL account.
+  /// Addresses that have never been used get a synthesized empty account.
+  pub fn get_account<BS, RT>(rt: &mut RT, address: H160) -> Result<RawBytes, ActorError>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    rt.validate_immediate_caller_accept_any()?;
+
+    RawBytes::serialize(Self::load_account(rt, &address)?).map_err(Into::into)
+  }
+
+  /// Returns the nonce of an EVM address, the equivalent of
+  /// `eth_getTransactionCount`.
+  ///
+  /// Actors cannot observe the nonce of a FIL account, so only the EVM-side
+  /// nonce is reported for linked EOAs.
+  pub fn get_nonce<BS, RT>(rt: &mut RT, address: H160) -> Result<RawBytes, ActorError>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    rt.validate_immediate_caller_accept_any()?;
+    RawBytes::serialize(Self::load_account(rt, &address)?.nonce).map_err(Into::into)
+  }
+
+  /// Returns the balance of an EVM address, the equivalent of
+  /// `eth_getBalance`.
+  ///
+  /// For EOAs this is the sum of the EVM-side and linked FIL balances.
+  pub fn get_balance<BS, RT>(rt: &mut RT, address: H160) -> Result<RawBytes, ActorError>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    rt.validate_immediate_caller_accept_any()?;
+    RawBytes::serialize(Self::load_account(rt, &address)?.balance).map_err(Into::into)
+  }
+
+  /// Credits the FIL attached to the message to an EVM address.
+  pub fn deposit<BS, RT>(rt: &mut RT, address: H160) -> Result<(), ActorError>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    rt.validate_immediate_caller_accept_any()?;
+    deposit(rt, address).map_err(|e| ActorError::illegal_argument(format!("{e:?}")))
+  }
+
+  /// Moves FIL from the EVM-side balance of an account to a Filecoin
+  /// address, authorized by a transaction signed by that account.
+  pub fn withdraw<BS, RT>(rt: &mut RT, params: WithdrawParams) -> Result<(), ActorError>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    rt.validate_immediate_caller_accept_any()?;
+    withdraw(rt, params).map_err(|e| ActorError::illegal_argument(format!("{e:?}")))
+  }
+
+  fn load_account<BS, RT>(rt: &RT, address: &H160) -> Result<EthereumAccount, ActorError>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    state::BridgeState::load(rt)
+      .and_then(|state| state.account(rt, address))
+      .and_then(|account| unified_account(rt, account))
+      .map_err(|e| ActorError::illegal_state(format!("{e:?}")))
+  }
 }
 
 impl ActorCode for BridgeActor {
@@ -91,14 +247,26 @@ impl ActorCode for BridgeActor {
   {
     match FromPrimitive::from_u64(method) {
       Some(Method::Constructor) => {
-        let runtime_cid: Cid = from_slice(&params)?;
-        Self::constructor(rt, runtime_cid)?;
+        Self::constructor(rt, from_slice(&params)?)?;
         Ok(RawBytes::default())
       }
       Some(Method::ProcessTransaction) => {
         let rlp: Vec<u8> = from_slice(&params)?;
         Self::process_transaction(rt, &rlp)
       }
+      Some(Method::Call) => Self::call(rt, from_slice(&params)?),
+      Some(Method::EstimateGas) => Self::estimate_gas(rt, from_slice(&params)?),
+      Some(Method::GetAccount) => Self::get_account(rt, from_slice(&params)?),
+      Some(Method::GetNonce) => Self::get_nonce(rt, from_slice(&params)?),
+      Some(Method::GetBalance) => Self::get_balance(rt, from_slice(&params)?),
+      Some(Method::Deposit) => {
+        Self::deposit(rt, from_slice(&params)?)?;
+        Ok(RawBytes::default())
+      }
+      Some(Method::Withdraw) => {
+        Self::withdraw(rt, from_slice(&params)?)?;
+        Ok(RawBytes::default())
+      }
       None => Err(actor_error!(unhandled_message; "Invalid method")),
     }
   }
@@ -0,0 +1,55 @@
+use {
+  crate::state,
+  anyhow::anyhow,
+  fil_actors_runtime::runtime::Runtime,
+  fvm_evm::{AccountKind, SignedTransaction},
+  fvm_ipld_blockstore::Blockstore,
+  fvm_shared::address::Address,
+};
+
+/// Links the sender of a transaction to its Filecoin f1 address.
+///
+/// The public key recovered from the transaction signature is the same
+/// secp256k1 key that controls the f1 address (Blake2B hash of the key),
+/// so the first transaction sent from an EOA is enough to discover its FIL
+/// equivalent. Accounts that are already linked are left untouched.
+pub fn link_sender_account<BS, RT>(
+  rt: &mut RT,
+  tx: &SignedTransaction,
+) -> anyhow::Result<()>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  let sender = tx.sender_address()?;
+
+  let mut bridge_state = state::BridgeState::load(rt)?;
+  let mut bridge_accounts_map = bridge_state.accounts(rt)?;
+  let mut account = bridge_accounts_map
+    .get(&sender)?
+    .cloned()
+    .unwrap_or_default();
+
+  match account.kind {
+    AccountKind::ExternallyOwned {
+      fil_account: Some(_),
+    } => return Ok(()),
+    AccountKind::ExternallyOwned { fil_account: None } => {}
+    // there is no private key for contract addresses (EIP-3607)
+    AccountKind::Contract { .. } => {
+      return Err(anyhow!(
+        "{sender:?} is a contract and cannot send transactions"
+      ))
+    }
+  }
+
+  let public_key = tx.sender_public_key()?;
+  account.kind = AccountKind::ExternallyOwned {
+    fil_account: Some(Address::new_secp256k1(&public_key)?),
+  };
+
+  bridge_accounts_map.set(sender, account)?;
+  bridge_state.update_accounts(&mut bridge_accounts_map)?;
+
+  Ok(())
+}
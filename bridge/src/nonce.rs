@@ -0,0 +1,56 @@
+use {
+  crate::state,
+  anyhow::anyhow,
+  fil_actors_runtime::runtime::Runtime,
+  fvm_evm::{SignedTransaction, H160},
+  fvm_ipld_blockstore::Blockstore,
+};
+
+/// Consumes the nonce of a signed transaction and returns its sender.
+///
+/// The transaction must be bound to the chain id of this bridge (EIP-155)
+/// and carry the next nonce of its sender, which is then incremented. Every
+/// method that acts on a signed transaction calls this before moving any
+/// value, so the same signed bytes can only ever be used once.
+pub fn use_nonce<BS, RT>(rt: &mut RT, tx: &SignedTransaction) -> anyhow::Result<H160>
+where
+  BS: Blockstore,
+  RT: Runtime<BS>,
+{
+  let mut bridge_state = state::BridgeState::load(rt)?;
+  match tx.chain_id() {
+    Some(chain_id) if chain_id == bridge_state.chain_id() => {}
+    Some(chain_id) => {
+      return Err(anyhow!(
+        "transaction is signed for chain {chain_id}, expected {}",
+        bridge_state.chain_id()
+      ))
+    }
+    None => return Err(anyhow!("transaction is not replay protected (EIP-155)")),
+  }
+
+  let sender = tx.sender_address()?;
+  let mut bridge_accounts_map = bridge_state.accounts(rt)?;
+  let mut account = bridge_accounts_map
+    .get(&sender)?
+    .cloned()
+    .unwrap_or_default();
+
+  if tx.nonce() != account.nonce {
+    return Err(anyhow!(
+      "invalid nonce for {sender:?}: expected {}, got {}",
+      account.nonce,
+      tx.nonce()
+    ));
+  }
+
+  // nonces are capped at 2^64 - 1 (EIP-2681)
+  account.nonce = account
+    .nonce
+    .checked_add(1)
+    .ok_or_else(|| anyhow!("nonce of {sender:?} is exhausted"))?;
+  bridge_accounts_map.set(sender, account)?;
+  bridge_state.update_accounts(&mut bridge_accounts_map)?;
+
+  Ok(sender)
+}
@@ -2,7 +2,7 @@ use {
   anyhow::Context,
   cid::Cid,
   fil_actors_runtime::runtime::Runtime,
-  fvm_evm::{EthereumAccount, H160},
+  fvm_evm::{EthereumAccount, Revision, H160},
   fvm_ipld_blockstore::Blockstore,
   fvm_ipld_encoding::{to_vec, Cbor, CborStore, DAG_CBOR},
   fvm_ipld_hamt::Hamt,
@@ -18,12 +18,23 @@ pub struct BridgeState {
 
   /// Hamt H160 -> EthereumAccount
   accounts: Cid,
+
+  /// The hard fork rules all EVM code is executed under.
+  revision: Revision,
+
+  /// Chain id that signed transactions must be bound to (EIP-155).
+  chain_id: u64,
 }
 
 impl Cbor for BridgeState {}
 
 impl BridgeState {
-  pub fn create<BS, RT>(rt: &RT, runtime_cid: &Cid) -> anyhow::Result<(Self, Cid)>
+  pub fn create<BS, RT>(
+    rt: &RT,
+    runtime_cid: &Cid,
+    revision: Revision,
+    chain_id: u64,
+  ) -> anyhow::Result<(Self, Cid)>
   where
     BS: Blockstore,
     RT: Runtime<BS>,
@@ -31,6 +42,8 @@ impl BridgeState {
     let instance = BridgeState {
       runtime_cid: *runtime_cid,
       accounts: Hamt::<_, EthereumAccount, H160>::new(rt.store()).flush()?,
+      revision,
+      chain_id,
     };
 
     let serialized = to_vec(&instance)?;
@@ -64,10 +77,49 @@ impl BridgeState {
     )?)
   }
 
+  /// Returns the account stored under `address`, or a synthesized
+  /// empty account if the address has never been used.
+  pub fn account<BS, RT>(
+    &self,
+    rt: &RT,
+    address: &H160,
+  ) -> anyhow::Result<EthereumAccount>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    Ok(
+      self
+        .accounts(rt)?
+        .get(address)?
+        .cloned()
+        .unwrap_or_default(),
+    )
+  }
+
   pub fn runtime_cid(&self) -> &Cid {
     &self.runtime_cid
   }
 
+  pub fn revision(&self) -> Revision {
+    self.revision
+  }
+
+  pub fn chain_id(&self) -> u64 {
+    self.chain_id
+  }
+
+  /// Returns the current state root of the bridge, which [`Self::restore`]
+  /// rolls back to.
+  pub fn snapshot() -> anyhow::Result<Cid> {
+    Ok(sself::root()?)
+  }
+
+  /// Discards all changes made to the bridge state since `snapshot`.
+  pub fn restore(snapshot: &Cid) -> anyhow::Result<()> {
+    Ok(sself::set_root(snapshot)?)
+  }
+
   pub fn update_accounts<BS: Blockstore>(
     &mut self,
     accounts: &mut Hamt<BS, EthereumAccount, H160>,
@@ -1,17 +1,69 @@
 use {
-  fil_actors_runtime::runtime::Runtime,
-  fvm_evm::SignedTransaction,
+  crate::{
+    balance::{credit, debit},
+    state,
+  },
+  fil_actors_runtime::{runtime::Runtime, BURNT_FUNDS_ACTOR_ADDR},
+  fvm_evm::{h160_to_fil_address, u256_to_token, SignedTransaction, TransactionAction},
   fvm_ipld_blockstore::Blockstore,
   fvm_ipld_encoding::RawBytes,
+  fvm_shared::METHOD_SEND,
 };
 
+/// Moves value between accounts without running any EVM code.
+///
+/// Value sent to an EOA is credited to its EVM-side balance, value sent to
+/// an address embedding an actor ID is sent to that actor and value sent
+/// to the zero address without any input is burned.
 pub fn transfer_tokens<BS, RT>(
-  _rt: &mut RT,
-  _tx: SignedTransaction,
+  rt: &mut RT,
+  tx: SignedTransaction,
 ) -> anyhow::Result<RawBytes>
 where
   BS: Blockstore,
   RT: Runtime<BS>,
 {
-  todo!()
+  let sender = tx.sender_address()?;
+  let mut bridge_state = state::BridgeState::load(rt)?;
+  let mut bridge_accounts_map = bridge_state.accounts(rt)?;
+
+  let mut from = bridge_accounts_map
+    .get(&sender)?
+    .cloned()
+    .unwrap_or_default();
+  debit(rt, sender, &mut from, tx.value())?;
+  bridge_accounts_map.set(sender, from)?;
+
+  match tx.action() {
+    TransactionAction::Call(to) => match h160_to_fil_address(&to) {
+      // the value leaves the EVM world towards a native actor
+      Some(actor) => {
+        rt.send(
+          actor,
+          METHOD_SEND,
+          RawBytes::default(),
+          u256_to_token(tx.value()),
+        )?;
+      }
+      None => {
+        let mut recipient = bridge_accounts_map.get(&to)?.cloned().unwrap_or_default();
+        credit(&mut recipient, tx.value())?;
+        bridge_accounts_map.set(to, recipient)?;
+      }
+    },
+    TransactionAction::Create => {
+      // the bridge holds the FIL backing all EVM-side balances
+      if !tx.value().is_zero() {
+        rt.send(
+          *BURNT_FUNDS_ACTOR_ADDR,
+          METHOD_SEND,
+          RawBytes::default(),
+          u256_to_token(tx.value()),
+        )?;
+      }
+    }
+  }
+
+  bridge_state.update_accounts(&mut bridge_accounts_map)?;
+  Ok(RawBytes::default())
 }
@@ -5,23 +5,42 @@ use {
     runtime::{ActorCode, Runtime},
     ActorError,
   },
-  fvm_evm::EvmContractRuntimeConstructor,
+  fvm_evm::{
+    execute,
+    fil_address_to_h160,
+    token_to_u256,
+    AccessListItem,
+    ActorCallParams,
+    Bytecode,
+    CallKind,
+    CallReturn,
+    EvmContractRuntimeConstructor,
+    ExecutionState,
+    InvocationParams,
+    InvocationReturn,
+    Message,
+    System,
+    TransactionContext,
+    H256,
+    MAX_CODE_SIZE,
+    U256,
+  },
   fvm_ipld_blockstore::Blockstore,
   fvm_ipld_encoding::{from_slice, RawBytes},
   fvm_shared::{MethodNum, METHOD_CONSTRUCTOR},
   num_derive::FromPrimitive,
   num_traits::FromPrimitive,
+  sha3::{Digest, Keccak256},
 };
 
 mod state;
 
+/// Bridge actor method that credits attached FIL to an EVM address.
+const BRIDGE_DEPOSIT_METHOD_NUM: u64 = 8;
+
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(EvmRuntimeActor);
 
-/// Maximum allowed EVM bytecode size.
-/// The contract code size limit is 24kB.
-const MAX_CODE_SIZE: usize = 0x6000;
-
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
@@ -30,6 +49,8 @@ pub enum Method {
   GetStorageValue = 3,
   GetCodeHash = 4,
   GetCodeSize = 5,
+  SimulateCall = 6,
+  DeleteContract = 7,
 }
 
 pub struct EvmRuntimeActor;
@@ -69,82 +90,273 @@ impl EvmRuntimeActor {
       args.registry,
       args.address,
       args.initial_state,
+      args.revision,
     )
     .map_err(|e| ActorError::illegal_state(e.to_string()))?;
 
     Ok(())
   }
 
-  pub fn invoke_contract<BS, RT>(rt: &mut RT) -> Result<RawBytes, ActorError>
+  /// Executes a message against this contract and persists the resulting
+  /// storage changes if execution succeeds.
+  ///
+  /// The bridge sends `InvocationParams` built from signed Ethereum
+  /// transactions and receives an `InvocationReturn`. Any other actor sends
+  /// `ActorCallParams`, in which case the caller shows up as an ID-embedding
+  /// EVM address and the output data is returned directly.
+  pub fn invoke_contract<BS, RT>(
+    rt: &mut RT,
+    params: &RawBytes,
+  ) -> Result<RawBytes, ActorError>
   where
     BS: Blockstore,
     RT: Runtime<BS>,
   {
     rt.validate_immediate_caller_accept_any()?;
-    // let state: ContractState = rt.state()?;
-    // let message = Message {
-    //   kind: fvm_evm::CallKind::Call,
-    //   is_static: false,
-    //   depth: 1,
-    //   gas: 2100,
-    //   recipient: H160::zero(),
-    //   sender: H160::zero(),
-    //   input_data: Bytes::new(),
-    //   value: U256::zero(),
-    // };
-
-    // let bytecode: Vec<_> = from_slice(&ipld::get(&state.bytecode).map_err(|e| {
-    //   ActorError::illegal_state(format!("failed to load bytecode: {e:?}"))
-    // })?)
-    // .map_err(|e| ActorError::unspecified(format!("failed to load bytecode:
-    // {e:?}")))?;
-
-    // // EVM contract bytecode
-    // let bytecode = Bytecode::new(&bytecode)
-    //   .map_err(|e| ActorError::unspecified(format!("invalid bytecode: {e:?}")))?;
-
-    // // the execution state of the EVM, stack, heap, etc.
-    // let mut runtime = ExecutionState::new(&message);
-
-    // // the interface between the EVM interpretter and the FVM system
-    // let mut system = System::new(state.state, rt, state.bridge,
-    // state.self_address)   .map_err(|e|
-    // ActorError::unspecified(format!("failed to create runtime: {e:?}")))?;
-
-    // // invoke the bytecode using the current state and the platform interface
-    // let output = execute(&bytecode, &mut runtime, &mut system)
-    //   .map_err(|e| ActorError::unspecified(format!("contract execution error:
-    // {e:?}")))?;
-
-    // log(format!("evm output: {output:?}"));
+    let state: ContractState = rt.state()?;
+
+    if rt.message().caller() == state.bridge {
+      let InvocationParams {
+        message,
+        context,
+        access_list,
+      } = from_slice(params)?;
+      let output = Self::execute_message(rt, &state, message, context, &access_list)?;
+      RawBytes::serialize(output).map_err(Into::into)
+    } else {
+      Self::invoke_from_actor(rt, &state, from_slice(params)?)
+    }
+  }
+
+  /// Handles `InvokeContract` called by a native actor or account.
+  fn invoke_from_actor<BS, RT>(
+    rt: &mut RT,
+    state: &ContractState,
+    params: ActorCallParams,
+  ) -> Result<RawBytes, ActorError>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    // the immediate caller is always reported as an ID address
+    let sender = fil_address_to_h160(&rt.message().caller()).ok_or_else(|| {
+      ActorError::illegal_argument("caller must be an ID address".into())
+    })?;
+
+    // the bridge holds the FIL backing EVM balances, so the attached value
+    // is deposited to this contract before it can observe it.
+    let attached = rt.message().value_received();
+    let value = token_to_u256(&attached)
+      .ok_or_else(|| ActorError::illegal_argument(format!("invalid value {attached}")))?;
+    if !value.is_zero() {
+      rt.send(
+        state.bridge,
+        BRIDGE_DEPOSIT_METHOD_NUM,
+        RawBytes::serialize(state.self_address)?,
+        attached,
+      )?;
+    }
+
+    let message = Message {
+      kind: CallKind::Call,
+      is_static: false,
+      depth: 0,
+      gas: params.gas.try_into().unwrap_or(i64::MAX),
+      recipient: state.self_address,
+      sender,
+      input_data: params.input_data,
+      value,
+    };
+
+    // native messages have no gas price or Ethereum chain id
+    let context =
+      TransactionContext::new(sender, U256::zero(), U256::zero(), state.revision)?;
+    let output = Self::execute_message(rt, state, message, context, &[])?;
+
+    // returning an error reverts all changes made by this message
+    if !output.result.is_success() {
+      return Err(ActorError::unspecified(format!(
+        "EVM execution failed: {}",
+        output.result.error_message()
+      )));
+    }
+    if output.selfdestruct.is_some() {
+      return Err(ActorError::forbidden(
+        "SELFDESTRUCT is only supported in transactions sent through the bridge".into(),
+      ));
+    }
+
+    RawBytes::serialize(output.result.output_data).map_err(Into::into)
+  }
+
+  fn execute_message<BS, RT>(
+    rt: &mut RT,
+    state: &ContractState,
+    mut message: Message,
+    context: TransactionContext,
+    access_list: &[AccessListItem],
+  ) -> Result<InvocationReturn, ActorError>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    let bytecode = Self::load_bytecode(rt)?;
+    let bytecode = Bytecode::new(&bytecode)
+      .map_err(|e| ActorError::unspecified(format!("invalid bytecode: {e:?}")))?;
+
+    message.recipient = state.self_address;
+
+    // the interface between the EVM interpretter and the FVM system
+    let system = System::with_context(
+      state.state,
+      rt,
+      state.bridge,
+      state.self_address,
+      context,
+      access_list,
+    )
+    .map_err(|e| ActorError::unspecified(format!("failed to create runtime: {e:?}")))?;
+
+    // the execution state of the EVM, stack, heap, etc.
+    let mut exec_state = ExecutionState::new(&message);
+
+    // invoke the bytecode using the current state and the platform interface
+    let result = CallReturn::new(&message, execute(&bytecode, &mut exec_state, &system));
+
+    let mut selfdestruct = None;
+    let mut logs = Vec::new();
+    if result.is_success() {
+      let state_root = system.flush_state()?;
+      selfdestruct = system.selfdestruct_beneficiary();
+      logs = system.logs();
+      drop(system);
+
+      rt.transaction(|st: &mut ContractState, _| {
+        st.state = state_root;
+        Ok(())
+      })?;
+    }
+
+    Ok(InvocationReturn {
+      revert_reason: result.revert_reason().map(|reason| reason.to_string()),
+      result,
+      selfdestruct,
+      logs,
+    })
+  }
+
+  /// Deletes this actor after the bridge has moved the balance of the
+  /// self-destructed contract to its beneficiary. Any FIL held by the actor
+  /// itself goes back to the bridge.
+  pub fn delete_contract<BS, RT>(rt: &mut RT) -> Result<RawBytes, ActorError>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    let state: ContractState = rt.state()?;
+    rt.validate_immediate_caller_is(std::iter::once(&state.bridge))?;
+    rt.delete_actor(&state.bridge)?;
     Ok(RawBytes::default())
   }
 
-  pub fn get_storage_value<BS, RT>(rt: &mut RT) -> Result<RawBytes, ActorError>
+  /// Returns the value stored under `key` in the contract storage.
+  ///
+  /// Keys that were never written (or were cleared) read as zero,
+  /// same as SLOAD would observe them.
+  pub fn get_storage_value<BS, RT>(rt: &mut RT, key: U256) -> Result<RawBytes, ActorError>
   where
     BS: Blockstore,
     RT: Runtime<BS>,
   {
     rt.validate_immediate_caller_accept_any()?;
-    todo!()
+    let state: ContractState = rt.state()?;
+    let storage = state
+      .storage(rt.store())
+      .map_err(|e| ActorError::illegal_state(format!("failed to load storage: {e:?}")))?;
+
+    let value = storage
+      .get(&key)
+      .map_err(|e| ActorError::illegal_state(format!("failed to read storage: {e:?}")))?
+      .cloned()
+      .unwrap_or_default();
+
+    RawBytes::serialize(value).map_err(Into::into)
   }
 
+  /// Returns keccak256 of the contract bytecode.
   pub fn get_code_hash<BS, RT>(rt: &mut RT) -> Result<RawBytes, ActorError>
   where
     BS: Blockstore,
     RT: Runtime<BS>,
   {
     rt.validate_immediate_caller_accept_any()?;
-    todo!()
+    let bytecode = Self::load_bytecode(rt)?;
+    let hash = H256::from_slice(Keccak256::digest(&bytecode).as_slice());
+    RawBytes::serialize(hash).map_err(Into::into)
   }
 
+  /// Returns the length of the contract bytecode in bytes.
   pub fn get_code_size<BS, RT>(rt: &mut RT) -> Result<RawBytes, ActorError>
   where
     BS: Blockstore,
     RT: Runtime<BS>,
   {
     rt.validate_immediate_caller_accept_any()?;
-    todo!()
+    let bytecode = Self::load_bytecode(rt)?;
+    RawBytes::serialize(bytecode.len() as u64).map_err(Into::into)
+  }
+
+  /// Executes a message against the current contract state without
+  /// persisting any of its effects.
+  ///
+  /// The state root of this actor is never updated here, so all storage
+  /// writes performed by the contract are discarded once execution ends.
+  pub fn simulate_call<BS, RT>(
+    rt: &mut RT,
+    params: InvocationParams,
+  ) -> Result<RawBytes, ActorError>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    rt.validate_immediate_caller_accept_any()?;
+    let state: ContractState = rt.state()?;
+    let bytecode = Self::load_bytecode(rt)?;
+    let bytecode = Bytecode::new(&bytecode)
+      .map_err(|e| ActorError::unspecified(format!("invalid bytecode: {e:?}")))?;
+
+    let InvocationParams {
+      mut message,
+      context,
+      access_list,
+    } = params;
+    message.recipient = state.self_address;
+
+    let system = System::with_context(
+      state.state,
+      rt,
+      state.bridge,
+      state.self_address,
+      context,
+      &access_list,
+    )
+    .map_err(|e| ActorError::unspecified(format!("failed to create runtime: {e:?}")))?;
+
+    let mut exec_state = ExecutionState::new(&message);
+    let output = execute(&bytecode, &mut exec_state, &system);
+
+    RawBytes::serialize(CallReturn::new(&message, output)).map_err(Into::into)
+  }
+
+  fn load_bytecode<BS, RT>(rt: &RT) -> Result<Vec<u8>, ActorError>
+  where
+    BS: Blockstore,
+    RT: Runtime<BS>,
+  {
+    let state: ContractState = rt.state()?;
+    state
+      .bytecode(rt.store())
+      .map_err(|e| ActorError::illegal_state(format!("failed to load bytecode: {e:?}")))
   }
 }
 
@@ -163,10 +375,12 @@ impl ActorCode for EvmRuntimeActor {
         Self::constructor(rt, &from_slice(&params)?)?;
         Ok(RawBytes::default())
       }
-      Some(Method::InvokeContract) => Self::invoke_contract(rt),
-      Some(Method::GetStorageValue) => Self::get_storage_value(rt),
+      Some(Method::InvokeContract) => Self::invoke_contract(rt, params),
+      Some(Method::GetStorageValue) => Self::get_storage_value(rt, from_slice(&params)?),
       Some(Method::GetCodeHash) => Self::get_code_hash(rt),
       Some(Method::GetCodeSize) => Self::get_code_size(rt),
+      Some(Method::SimulateCall) => Self::simulate_call(rt, from_slice(&params)?),
+      Some(Method::DeleteContract) => Self::delete_contract(rt),
       None => Err(actor_error!(unhandled_message; "Invalid method")),
     }
   }
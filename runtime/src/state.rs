@@ -1,7 +1,10 @@
 use {
+  anyhow::Context,
   cid::Cid,
-  fvm_evm::{abort, H160},
-  fvm_ipld_encoding::{to_vec, Cbor, RawBytes, DAG_CBOR},
+  fvm_evm::{abort, Revision, H160, U256},
+  fvm_ipld_blockstore::Blockstore,
+  fvm_ipld_encoding::{to_vec, Cbor, CborStore, RawBytes, DAG_CBOR},
+  fvm_ipld_hamt::Hamt,
   fvm_sdk::{ipld, sself},
   fvm_shared::address::Address,
   multihash::Code,
@@ -28,6 +31,9 @@ pub struct ContractState {
 
   /// EVM address of the current contract
   pub self_address: H160,
+
+  /// The hard fork rules of the bridge that created this contract.
+  pub revision: Revision,
 }
 
 impl Cbor for ContractState {}
@@ -42,10 +48,12 @@ impl ContractState {
     bridge: Address,
     self_address: H160,
     initial_state: Cid,
+    revision: Revision,
   ) -> anyhow::Result<Self> {
     let this = Self {
       bridge,
       self_address,
+      revision,
       bytecode: ipld::put(
         Code::Blake2b256.into(),
         32,
@@ -64,6 +72,21 @@ impl ContractState {
     Ok(this)
   }
 
+  /// Loads the contract bytecode that was stored by the constructor.
+  pub fn bytecode<BS: Blockstore>(&self, store: &BS) -> anyhow::Result<Vec<u8>> {
+    store
+      .get_cbor(&self.bytecode)?
+      .context("contract bytecode not found")
+  }
+
+  /// Loads the contract storage HAMT rooted at the current state root.
+  pub fn storage<'s, BS: Blockstore>(
+    &self,
+    store: &'s BS,
+  ) -> anyhow::Result<Hamt<&'s BS, U256, U256>> {
+    Ok(Hamt::<_, U256, U256>::load(&self.state, store)?)
+  }
+
   pub fn _save(&self) -> Cid {
     let serialized = match to_vec(self) {
       Ok(s) => s,
@@ -0,0 +1,279 @@
+//! Interpreter benchmarks.
+//!
+//! Every workload runs through [`execute`] against an in-process host
+//! that keeps the storage of the contract under test in a HAMT, as
+//! [`System`](fvm_evm::System) does on chain. Throughput is reported in
+//! gas: criterion's elements per second are gas per second.
+
+use {
+  bytes::Bytes,
+  criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput},
+  fvm_evm::{
+    abi::{self, Function, Token},
+    execute,
+    testing::InMemoryHost,
+    AccessStatus,
+    Bytecode,
+    Call,
+    CallKind,
+    ExecutionState,
+    Host,
+    Message,
+    Output,
+    StatusCode,
+    StorageStatus,
+    TransactionContext,
+    H160,
+    U256,
+  },
+  fvm_ipld_blockstore::MemoryBlockstore,
+  fvm_ipld_hamt::Hamt,
+  hex_literal::hex,
+  sha3::{Digest, Keccak256},
+  std::{cell::RefCell, collections::HashMap},
+};
+
+const GAS: i64 = 30_000_000;
+const CONTRACT: H160 = H160([0x10; 20]);
+const SENDER: H160 = H160([0x20; 20]);
+
+/// Delegates to an [`InMemoryHost`], except for the storage of
+/// [`CONTRACT`], which lives in a HAMT over an in-memory blockstore.
+struct HamtHost {
+  accounts: InMemoryHost,
+  storage: RefCell<Hamt<MemoryBlockstore, U256, U256>>,
+  original_storage: RefCell<HashMap<U256, U256>>,
+}
+
+impl HamtHost {
+  fn new(code: impl Into<Bytes>) -> Self {
+    let mut accounts = InMemoryHost::default();
+    accounts.context_mut().tx_origin = SENDER;
+    accounts.deploy(CONTRACT, code);
+    Self {
+      accounts,
+      storage: RefCell::new(Hamt::new(MemoryBlockstore::default())),
+      original_storage: RefCell::new(HashMap::new()),
+    }
+  }
+
+  /// Runs the code of [`CONTRACT`] with `input`, sent by [`SENDER`].
+  fn run(&self, input: &[u8]) -> Output {
+    let code = self.accounts.account(CONTRACT).unwrap().code;
+    self.run_code(&code, input)
+  }
+
+  fn run_code(&self, code: &[u8], input: &[u8]) -> Output {
+    let message = Message {
+      kind: CallKind::Call,
+      is_static: false,
+      depth: 0,
+      gas: GAS,
+      recipient: CONTRACT,
+      sender: SENDER,
+      input_data: Bytes::copy_from_slice(input),
+      value: U256::zero(),
+    };
+    self.access_account(SENDER);
+    self.access_account(CONTRACT);
+
+    let bytecode = Bytecode::new(code).unwrap();
+    let mut state = ExecutionState::new(&message);
+    let output = execute(&bytecode, &mut state, self).unwrap();
+    assert_eq!(output.status_code, StatusCode::Success);
+    assert!(!output.reverted);
+    output
+  }
+}
+
+impl Host for HamtHost {
+  fn account_exists(&self, address: H160) -> Result<bool, StatusCode> {
+    self.accounts.account_exists(address)
+  }
+
+  fn get_storage(&self, address: H160, key: U256) -> Result<U256, StatusCode> {
+    if address != CONTRACT {
+      return self.accounts.get_storage(address, key);
+    }
+    Ok(
+      self
+        .storage
+        .borrow()
+        .get(&key)
+        .map_err(|e| StatusCode::InternalError(e.to_string()))?
+        .cloned()
+        .unwrap_or_default(),
+    )
+  }
+
+  fn set_storage(
+    &self,
+    address: H160,
+    key: U256,
+    value: U256,
+  ) -> Result<StorageStatus, StatusCode> {
+    if address != CONTRACT {
+      return self.accounts.set_storage(address, key, value);
+    }
+
+    let current = self.get_storage(address, key)?;
+    let original = *self
+      .original_storage
+      .borrow_mut()
+      .entry(key)
+      .or_insert(current);
+    let mut storage = self.storage.borrow_mut();
+    match value.is_zero() {
+      true => storage.delete(&key).map(|_| ()),
+      false => storage.set(key, value).map(|_| ()),
+    }
+    .map_err(|e| StatusCode::InternalError(e.to_string()))?;
+
+    Ok(StorageStatus::new(original, current, value))
+  }
+
+  fn get_transient_storage(&self, address: H160, key: U256) -> U256 {
+    self.accounts.get_transient_storage(address, key)
+  }
+
+  fn set_transient_storage(&self, address: H160, key: U256, value: U256) {
+    self.accounts.set_transient_storage(address, key, value)
+  }
+
+  fn get_balance(&self, address: H160) -> Result<U256, StatusCode> {
+    self.accounts.get_balance(address)
+  }
+
+  fn get_code_size(&self, address: H160) -> Result<U256, StatusCode> {
+    self.accounts.get_code_size(address)
+  }
+
+  fn get_code_hash(&self, address: H160) -> Result<U256, StatusCode> {
+    self.accounts.get_code_hash(address)
+  }
+
+  fn copy_code(
+    &self,
+    address: H160,
+    offset: usize,
+    buffer: &mut [u8],
+  ) -> Result<usize, StatusCode> {
+    self.accounts.copy_code(address, offset, buffer)
+  }
+
+  fn selfdestruct(&self, address: H160, beneficiary: H160) -> Result<(), StatusCode> {
+    self.accounts.selfdestruct(address, beneficiary)
+  }
+
+  fn call(&self, msg: Call) -> Output {
+    self.accounts.call(msg)
+  }
+
+  fn get_block_hash(&self, block_number: u64) -> U256 {
+    self.accounts.get_block_hash(block_number)
+  }
+
+  fn emit_log(
+    &self,
+    address: H160,
+    data: Bytes,
+    topics: &[U256],
+  ) -> Result<(), StatusCode> {
+    self.accounts.emit_log(address, data, topics)
+  }
+
+  fn access_account(&self, address: H160) -> AccessStatus {
+    self.accounts.access_account(address)
+  }
+
+  fn access_storage(&self, address: H160, key: U256) -> AccessStatus {
+    self.accounts.access_storage(address, key)
+  }
+
+  fn transaction_context(&self) -> &TransactionContext {
+    self.accounts.transaction_context()
+  }
+}
+
+/// Benchmarks calling [`CONTRACT`] with `input` on hosts built by `setup`.
+///
+/// Every iteration gets a fresh host, so storage writes of one iteration
+/// do not change the cost of the next.
+fn bench(c: &mut Criterion, name: &str, setup: impl Fn() -> HamtHost, input: &[u8]) {
+  let gas_used = GAS - setup().run(input).gas_left;
+
+  let mut group = c.benchmark_group(name);
+  group.throughput(Throughput::Elements(gas_used as u64));
+  group.bench_function("execute", |b| {
+    b.iter_batched(&setup, |host| host.run(input), BatchSize::SmallInput)
+  });
+  group.finish();
+}
+
+fn arithmetic(c: &mut Criterion) {
+  // 10000 iterations of ((3 * 5 + 7) / 2) % 11
+  let code = hex!("6127105b600360050260070160029004600b900650600190038060035700");
+  bench(c, "arithmetic", || HamtHost::new(code.to_vec()), &[]);
+}
+
+fn keccak256(c: &mut Criterion) {
+  // 10000 iterations of hashing 256 bytes and storing the digest
+  let code = hex!("6127105b610100600020600052600190038060035700");
+  bench(c, "keccak256", || HamtHost::new(code.to_vec()), &[]);
+}
+
+fn memory_expansion(c: &mut Criterion) {
+  // a word store every 32 bytes, up to 1 MiB of memory
+  let code = hex!("60005b80805260200180621000001160025700");
+  bench(c, "memory_expansion", || HamtHost::new(code.to_vec()), &[]);
+}
+
+fn storage(c: &mut Criterion) {
+  // 1000 iterations of SSTORE(i, i) followed by SLOAD(i)
+  let code = hex!("6103e85b808055805450600190038060035700");
+  bench(c, "sstore_sload", || HamtHost::new(code.to_vec()), &[]);
+}
+
+fn simplecoin(c: &mut Criterion) {
+  let initcode =
+    hex::decode(include_str!("../../tests/contracts/simplecoin.hex").trim()).unwrap();
+  let runtime = {
+    let host = HamtHost::new(Bytes::new());
+    host.run_code(&initcode, &[]).output_data
+  };
+
+  // the constructor credits 10000 coins to the transaction origin, write
+  // that balance directly so the transfer starts with all slots cold
+  let slot = Keccak256::digest(abi::encode(&[
+    Token::Address(SENDER),
+    Token::Uint(0.into()),
+  ]));
+  let setup = || {
+    let host = HamtHost::new(runtime.clone());
+    host
+      .storage
+      .borrow_mut()
+      .set(U256::from_big_endian(&slot), 10000.into())
+      .unwrap();
+    host
+  };
+
+  let input = Function::parse("sendCoin(address,uint256)")
+    .unwrap()
+    .encode_input(&[
+      Token::Address(H160::repeat_byte(0x30)),
+      Token::Uint(1.into()),
+    ])
+    .unwrap();
+  bench(c, "simplecoin_transfer", setup, &input);
+}
+
+criterion_group!(
+  benches,
+  arithmetic,
+  keccak256,
+  memory_expansion,
+  storage,
+  simplecoin
+);
+criterion_main!(benches);
@@ -0,0 +1,49 @@
+//! Runs arbitrary code with arbitrary calldata through the interpreter.
+//!
+//! Execution may fail, but only by returning a `StatusCode`.
+
+#![no_main]
+
+use {
+  fvm_evm::{
+    execute,
+    testing::InMemoryHost,
+    Bytecode,
+    CallKind,
+    ExecutionState,
+    Message,
+    H160,
+    U256,
+  },
+  libfuzzer_sys::fuzz_target,
+};
+
+fuzz_target!(|input: (&[u8], &[u8])| {
+  let (code, calldata) = input;
+
+  let bytecode = match Bytecode::new(code) {
+    Ok(bytecode) => bytecode,
+    Err(_) => return,
+  };
+
+  let recipient = H160::repeat_byte(0x10);
+  let sender = H160::repeat_byte(0x20);
+
+  let host = InMemoryHost::default();
+  host.set_balance(recipient, U256::from(1_000_000));
+  host.deploy(recipient, code.to_vec());
+
+  let message = Message {
+    kind: CallKind::Call,
+    is_static: false,
+    depth: 0,
+    gas: 10_000_000,
+    recipient,
+    sender,
+    input_data: calldata.to_vec().into(),
+    value: U256::zero(),
+  };
+
+  let mut state = ExecutionState::new(&message);
+  let _ = execute(&bytecode, &mut state, &host);
+});
@@ -0,0 +1,47 @@
+//! Checks the signed 256-bit helpers against arbitrary precision integers.
+
+#![no_main]
+
+use {
+  fvm_evm::{
+    uints::{i256_cmp, i256_div, i256_mod},
+    U256,
+  },
+  libfuzzer_sys::fuzz_target,
+  num_bigint::{BigInt, Sign},
+};
+
+/// Interprets `value` as a two's complement signed integer.
+fn to_signed(value: U256) -> BigInt {
+  let mut bytes = [0u8; 32];
+  value.to_big_endian(&mut bytes);
+  BigInt::from_signed_bytes_be(&bytes)
+}
+
+/// Wraps `value` into 256 bits, two's complement.
+fn from_signed(value: BigInt) -> U256 {
+  let modulus = BigInt::from(1) << 256usize;
+  let value: BigInt = ((value % &modulus) + &modulus) % &modulus;
+  let (sign, bytes) = value.to_bytes_be();
+  match sign {
+    Sign::NoSign => U256::zero(),
+    _ => U256::from_big_endian(&bytes),
+  }
+}
+
+fuzz_target!(|input: ([u8; 32], [u8; 32])| {
+  let a = U256::from_big_endian(&input.0);
+  let b = U256::from_big_endian(&input.1);
+  let (x, y) = (to_signed(a), to_signed(b));
+
+  // division by zero is defined as zero in the EVM,
+  // otherwise results are truncated towards zero.
+  let (quotient, remainder) = match y == BigInt::from(0) {
+    true => (U256::zero(), U256::zero()),
+    false => (from_signed(&x / &y), from_signed(&x % &y)),
+  };
+
+  assert_eq!(i256_div(a, b), quotient, "{a:x} / {b:x}");
+  assert_eq!(i256_mod(a, b), remainder, "{a:x} % {b:x}");
+  assert_eq!(i256_cmp(a, b), x.cmp(&y), "{a:x} <=> {b:x}");
+});
@@ -0,0 +1,26 @@
+//! Decodes arbitrary bytes as a signed transaction.
+//!
+//! Decoding may fail, but must not panic, and whatever decodes must
+//! survive a round-trip through `serialize`.
+
+#![no_main]
+
+use {fvm_evm::SignedTransaction, libfuzzer_sys::fuzz_target};
+
+fuzz_target!(|data: &[u8]| {
+  let transaction = match SignedTransaction::try_from(data) {
+    Ok(transaction) => transaction,
+    Err(_) => return,
+  };
+
+  // the input may not be canonical, e.g. have trailing bytes,
+  // but its serialization must be.
+  let serialized = transaction.serialize();
+  let decoded = SignedTransaction::try_from(&serialized[..])
+    .expect("serialized transaction does not decode");
+
+  assert_eq!(decoded.serialize(), serialized);
+  assert_eq!(decoded.hash(), transaction.hash());
+  assert_eq!(decoded.transaction.hash(), transaction.transaction.hash());
+  assert_eq!(decoded.intrinsic_gas(), transaction.intrinsic_gas());
+});
@@ -0,0 +1,761 @@
+//! Solidity contract ABI encoding and decoding.
+//!
+//! Values are represented as [`Token`]s and their types as [`ParamType`]s.
+//! Encoding follows the head/tail layout described in the Solidity ABI
+//! specification, where dynamic values (`bytes`, `string`, `T[]` and any
+//! tuple or fixed size array containing them) are referenced from the head
+//! by their offset.
+
+use {
+  crate::{H160, U256},
+  sha3::{Digest, Keccak256},
+  std::fmt::{self, Display},
+};
+
+const WORD: usize = 32;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AbiError {
+  /// A type or function signature could not be parsed.
+  InvalidSignature(String),
+  /// A value does not match the type it is encoded as.
+  TypeMismatch,
+  /// The encoded data is truncated or malformed.
+  InvalidData,
+}
+
+impl Display for AbiError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      AbiError::InvalidSignature(s) => write!(f, "invalid ABI signature: {s}"),
+      AbiError::TypeMismatch => write!(f, "value does not match ABI type"),
+      AbiError::InvalidData => write!(f, "invalid ABI encoded data"),
+    }
+  }
+}
+
+impl std::error::Error for AbiError {}
+
+/// Type of an ABI encoded value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParamType {
+  Address,
+  Bool,
+  /// Unsigned integer of the given number of bits.
+  Uint(usize),
+  /// Signed integer of the given number of bits.
+  Int(usize),
+  /// `bytesN` of the given number of bytes.
+  FixedBytes(usize),
+  Bytes,
+  String,
+  Array(Box<ParamType>),
+  FixedArray(Box<ParamType>, usize),
+  Tuple(Vec<ParamType>),
+}
+
+impl ParamType {
+  /// Parses a Solidity type such as `uint256`, `bytes32[2]` or
+  /// `(address,string)[]`.
+  pub fn parse(s: &str) -> Result<Self, AbiError> {
+    let s = s.trim();
+    let invalid = || AbiError::InvalidSignature(s.to_string());
+
+    if let Some(inner) = s.strip_suffix(']') {
+      let open = inner.rfind('[').ok_or_else(invalid)?;
+      let element = Box::new(Self::parse(&inner[..open])?);
+      return match &inner[open + 1..] {
+        "" => Ok(ParamType::Array(element)),
+        len => Ok(ParamType::FixedArray(
+          element,
+          len.parse().map_err(|_| invalid())?,
+        )),
+      };
+    }
+
+    if let Some(inner) = s.strip_prefix('(') {
+      let inner = inner.strip_suffix(')').ok_or_else(invalid)?;
+      return Ok(ParamType::Tuple(parse_list(inner)?));
+    }
+
+    let bits = |digits: &str, default: usize| -> Result<usize, AbiError> {
+      let bits = match digits {
+        "" => default,
+        digits => digits.parse().map_err(|_| invalid())?,
+      };
+      match bits > 0 && bits <= 256 && bits % 8 == 0 {
+        true => Ok(bits),
+        false => Err(invalid()),
+      }
+    };
+
+    match s {
+      "address" => Ok(ParamType::Address),
+      "bool" => Ok(ParamType::Bool),
+      "bytes" => Ok(ParamType::Bytes),
+      "string" => Ok(ParamType::String),
+      _ => {
+        if let Some(len) = s.strip_prefix("bytes") {
+          match len.parse() {
+            Ok(len @ 1..=32) => Ok(ParamType::FixedBytes(len)),
+            _ => Err(invalid()),
+          }
+        } else if let Some(digits) = s.strip_prefix("uint") {
+          Ok(ParamType::Uint(bits(digits, 256)?))
+        } else if let Some(digits) = s.strip_prefix("int") {
+          Ok(ParamType::Int(bits(digits, 256)?))
+        } else {
+          Err(invalid())
+        }
+      }
+    }
+  }
+
+  /// True if values of this type are encoded in the tail.
+  pub fn is_dynamic(&self) -> bool {
+    match self {
+      ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
+      ParamType::FixedArray(element, _) => element.is_dynamic(),
+      ParamType::Tuple(types) => types.iter().any(ParamType::is_dynamic),
+      _ => false,
+    }
+  }
+
+  /// Number of bytes a value of this type occupies in the head.
+  fn head_size(&self) -> usize {
+    match self {
+      _ if self.is_dynamic() => WORD,
+      ParamType::FixedArray(element, len) => element.head_size() * len,
+      ParamType::Tuple(types) => types.iter().map(ParamType::head_size).sum(),
+      _ => WORD,
+    }
+  }
+}
+
+impl Display for ParamType {
+  /// Writes the canonical form used in function signatures.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ParamType::Address => write!(f, "address"),
+      ParamType::Bool => write!(f, "bool"),
+      ParamType::Uint(bits) => write!(f, "uint{bits}"),
+      ParamType::Int(bits) => write!(f, "int{bits}"),
+      ParamType::FixedBytes(len) => write!(f, "bytes{len}"),
+      ParamType::Bytes => write!(f, "bytes"),
+      ParamType::String => write!(f, "string"),
+      ParamType::Array(element) => write!(f, "{element}[]"),
+      ParamType::FixedArray(element, len) => write!(f, "{element}[{len}]"),
+      ParamType::Tuple(types) => write!(f, "({})", join(types)),
+    }
+  }
+}
+
+/// A value that can be ABI encoded.
+///
+/// Signed integers are stored in their two's complement representation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Token {
+  Address(H160),
+  Bool(bool),
+  Uint(U256),
+  Int(U256),
+  FixedBytes(Vec<u8>),
+  Bytes(Vec<u8>),
+  String(String),
+  Array(Vec<Token>),
+  FixedArray(Vec<Token>),
+  Tuple(Vec<Token>),
+}
+
+impl Token {
+  /// True if this value is encoded in the tail.
+  pub fn is_dynamic(&self) -> bool {
+    match self {
+      Token::Bytes(_) | Token::String(_) | Token::Array(_) => true,
+      Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+        tokens.iter().any(Token::is_dynamic)
+      }
+      _ => false,
+    }
+  }
+
+  /// Checks whether this value can be encoded as `param`.
+  pub fn type_check(&self, param: &ParamType) -> bool {
+    match (self, param) {
+      (Token::Address(_), ParamType::Address) => true,
+      (Token::Bool(_), ParamType::Bool) => true,
+      (Token::Uint(v), ParamType::Uint(bits)) => v.bits() <= *bits,
+      (Token::Int(v), ParamType::Int(bits)) => is_sign_extended(*v, *bits),
+      (Token::FixedBytes(b), ParamType::FixedBytes(len)) => b.len() == *len,
+      (Token::Bytes(_), ParamType::Bytes) => true,
+      (Token::String(_), ParamType::String) => true,
+      (Token::Array(tokens), ParamType::Array(element)) => {
+        tokens.iter().all(|t| t.type_check(element))
+      }
+      (Token::FixedArray(tokens), ParamType::FixedArray(element, len)) => {
+        tokens.len() == *len && tokens.iter().all(|t| t.type_check(element))
+      }
+      (Token::Tuple(tokens), ParamType::Tuple(types)) => {
+        tokens.len() == types.len()
+          && tokens.iter().zip(types).all(|(t, p)| t.type_check(p))
+      }
+      _ => false,
+    }
+  }
+
+  pub fn into_address(self) -> Option<H160> {
+    match self {
+      Token::Address(address) => Some(address),
+      _ => None,
+    }
+  }
+
+  pub fn into_uint(self) -> Option<U256> {
+    match self {
+      Token::Uint(v) | Token::Int(v) => Some(v),
+      _ => None,
+    }
+  }
+
+  pub fn into_bool(self) -> Option<bool> {
+    match self {
+      Token::Bool(b) => Some(b),
+      _ => None,
+    }
+  }
+
+  pub fn into_bytes(self) -> Option<Vec<u8>> {
+    match self {
+      Token::Bytes(bytes) | Token::FixedBytes(bytes) => Some(bytes),
+      _ => None,
+    }
+  }
+
+  pub fn into_string(self) -> Option<String> {
+    match self {
+      Token::String(s) => Some(s),
+      _ => None,
+    }
+  }
+
+  pub fn into_tokens(self) -> Option<Vec<Token>> {
+    match self {
+      Token::Array(tokens) | Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+        Some(tokens)
+      }
+      _ => None,
+    }
+  }
+}
+
+impl From<H160> for Token {
+  fn from(address: H160) -> Self {
+    Token::Address(address)
+  }
+}
+
+impl From<U256> for Token {
+  fn from(v: U256) -> Self {
+    Token::Uint(v)
+  }
+}
+
+impl From<bool> for Token {
+  fn from(b: bool) -> Self {
+    Token::Bool(b)
+  }
+}
+
+impl From<&str> for Token {
+  fn from(s: &str) -> Self {
+    Token::String(s.to_string())
+  }
+}
+
+/// A contract function, parsed from a signature like
+/// `transfer(address,uint256)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Function {
+  pub name: String,
+  pub inputs: Vec<ParamType>,
+}
+
+impl Function {
+  pub fn parse(signature: &str) -> Result<Self, AbiError> {
+    let invalid = || AbiError::InvalidSignature(signature.to_string());
+    let open = signature.find('(').ok_or_else(invalid)?;
+    let inputs = signature[open + 1..]
+      .strip_suffix(')')
+      .ok_or_else(invalid)?;
+    Ok(Self {
+      name: signature[..open].trim().to_string(),
+      inputs: parse_list(inputs)?,
+    })
+  }
+
+  /// The canonical signature, the preimage of the selector.
+  pub fn signature(&self) -> String {
+    format!("{}({})", self.name, join(&self.inputs))
+  }
+
+  pub fn selector(&self) -> [u8; 4] {
+    selector(&self.signature())
+  }
+
+  /// Encodes a call to this function, the selector followed by the
+  /// encoded arguments.
+  pub fn encode_input(&self, args: &[Token]) -> Result<Vec<u8>, AbiError> {
+    if args.len() != self.inputs.len()
+      || !args.iter().zip(&self.inputs).all(|(t, p)| t.type_check(p))
+    {
+      return Err(AbiError::TypeMismatch);
+    }
+    Ok(self.selector().into_iter().chain(encode(args)).collect())
+  }
+
+  /// Decodes the arguments of a call to this function.
+  pub fn decode_input(&self, data: &[u8]) -> Result<Vec<Token>, AbiError> {
+    match data.strip_prefix(&self.selector()) {
+      Some(args) => decode(&self.inputs, args),
+      None => Err(AbiError::InvalidData),
+    }
+  }
+}
+
+/// Returns the first four bytes of the keccak256 hash of a
+/// function or error signature.
+pub fn selector(signature: &str) -> [u8; 4] {
+  let hash = Keccak256::digest(signature.as_bytes());
+  [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Encodes a sequence of values, as found in function arguments.
+pub fn encode(tokens: &[Token]) -> Vec<u8> {
+  let mut out = Vec::new();
+  encode_sequence(tokens, &mut out);
+  out
+}
+
+/// Decodes a sequence of values of the given types.
+///
+/// Offsets may point to data that was already decoded, so the number of
+/// array elements and bytes decoded in total is bounded by the length of
+/// `data`, the most a well formed encoding can hold.
+pub fn decode(types: &[ParamType], data: &[u8]) -> Result<Vec<Token>, AbiError> {
+  let mut budget = data.len() / WORD;
+  decode_sequence(types, data, 0, &mut budget)
+}
+
+fn parse_list(s: &str) -> Result<Vec<ParamType>, AbiError> {
+  if s.trim().is_empty() {
+    return Ok(vec![]);
+  }
+
+  // split on commas that are not nested in a tuple
+  let mut types = vec![];
+  let (mut depth, mut start) = (0usize, 0);
+  for (i, c) in s.char_indices() {
+    match c {
+      '(' => depth += 1,
+      ')' => {
+        depth = depth
+          .checked_sub(1)
+          .ok_or_else(|| AbiError::InvalidSignature(s.to_string()))?
+      }
+      ',' if depth == 0 => {
+        types.push(ParamType::parse(&s[start..i])?);
+        start = i + 1;
+      }
+      _ => {}
+    }
+  }
+  types.push(ParamType::parse(&s[start..])?);
+  Ok(types)
+}
+
+fn join(types: &[ParamType]) -> String {
+  types
+    .iter()
+    .map(ToString::to_string)
+    .collect::<Vec<_>>()
+    .join(",")
+}
+
+/// True if `v`, in its two's complement representation, is the sign
+/// extension of a signed integer of `bits` bits.
+fn is_sign_extended(v: U256, bits: usize) -> bool {
+  let magnitude = match v.bit(255) {
+    true => !v,
+    false => v,
+  };
+  bits >= 256 || magnitude.bits() < bits
+}
+
+fn uint_word(v: U256) -> [u8; WORD] {
+  let mut word = [0u8; WORD];
+  v.to_big_endian(&mut word);
+  word
+}
+
+fn padded(bytes: &[u8], out: &mut Vec<u8>) {
+  out.extend_from_slice(bytes);
+  let rem = bytes.len() % WORD;
+  if rem != 0 {
+    out.resize(out.len() + WORD - rem, 0);
+  }
+}
+
+fn encode_sequence(tokens: &[Token], out: &mut Vec<u8>) {
+  let head_size: usize = tokens
+    .iter()
+    .map(|t| match t.is_dynamic() {
+      true => WORD,
+      false => static_size(t),
+    })
+    .sum();
+
+  let mut tail = Vec::new();
+  for token in tokens {
+    if token.is_dynamic() {
+      out.extend_from_slice(&uint_word(U256::from(head_size + tail.len())));
+      encode_token(token, &mut tail);
+    } else {
+      encode_token(token, out);
+    }
+  }
+  out.extend_from_slice(&tail);
+}
+
+fn static_size(token: &Token) -> usize {
+  match token {
+    Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+      tokens.iter().map(static_size).sum()
+    }
+    _ => WORD,
+  }
+}
+
+fn encode_token(token: &Token, out: &mut Vec<u8>) {
+  match token {
+    Token::Address(address) => {
+      out.extend_from_slice(&[0u8; 12]);
+      out.extend_from_slice(address.as_bytes());
+    }
+    Token::Bool(b) => out.extend_from_slice(&uint_word(U256::from(*b as u8))),
+    Token::Uint(v) | Token::Int(v) => out.extend_from_slice(&uint_word(*v)),
+    Token::FixedBytes(bytes) => padded(bytes, out),
+    Token::Bytes(bytes) => {
+      out.extend_from_slice(&uint_word(U256::from(bytes.len())));
+      padded(bytes, out);
+    }
+    Token::String(s) => {
+      out.extend_from_slice(&uint_word(U256::from(s.len())));
+      padded(s.as_bytes(), out);
+    }
+    Token::Array(tokens) => {
+      out.extend_from_slice(&uint_word(U256::from(tokens.len())));
+      encode_sequence(tokens, out);
+    }
+    Token::FixedArray(tokens) | Token::Tuple(tokens) => encode_sequence(tokens, out),
+  }
+}
+
+fn word_at(data: &[u8], at: usize) -> Result<&[u8], AbiError> {
+  at.checked_add(WORD)
+    .and_then(|end| data.get(at..end))
+    .ok_or(AbiError::InvalidData)
+}
+
+fn usize_at(data: &[u8], at: usize) -> Result<usize, AbiError> {
+  let v = U256::from_big_endian(word_at(data, at)?);
+  match v > U256::from(data.len()) {
+    true => Err(AbiError::InvalidData), // can never point inside of data
+    false => Ok(v.as_usize()),
+  }
+}
+
+/// Takes `words` from the number of words that decoded values may still
+/// claim, failing once the data could not possibly hold them.
+fn claim(budget: &mut usize, words: usize) -> Result<(), AbiError> {
+  *budget = budget.checked_sub(words).ok_or(AbiError::InvalidData)?;
+  Ok(())
+}
+
+fn decode_sequence(
+  types: &[ParamType],
+  data: &[u8],
+  base: usize,
+  budget: &mut usize,
+) -> Result<Vec<Token>, AbiError> {
+  let mut head = base;
+  let mut tokens = Vec::with_capacity(types.len());
+  for param in types {
+    if param.is_dynamic() {
+      let offset = base
+        .checked_add(usize_at(data, head)?)
+        .ok_or(AbiError::InvalidData)?;
+      tokens.push(decode_token(param, data, offset, budget)?);
+    } else {
+      tokens.push(decode_token(param, data, head, budget)?);
+    }
+    head += param.head_size();
+  }
+  Ok(tokens)
+}
+
+fn decode_bytes(data: &[u8], at: usize, budget: &mut usize) -> Result<Vec<u8>, AbiError> {
+  let len = usize_at(data, at)?;
+  claim(budget, (len + WORD - 1) / WORD)?;
+  let start = at + WORD;
+  start
+    .checked_add(len)
+    .and_then(|end| data.get(start..end))
+    .map(<[u8]>::to_vec)
+    .ok_or(AbiError::InvalidData)
+}
+
+fn decode_token(
+  param: &ParamType,
+  data: &[u8],
+  at: usize,
+  budget: &mut usize,
+) -> Result<Token, AbiError> {
+  match param {
+    ParamType::Address => {
+      let word = word_at(data, at)?;
+      match word[..12].iter().all(|b| *b == 0) {
+        true => Ok(Token::Address(H160::from_slice(&word[12..]))),
+        false => Err(AbiError::InvalidData),
+      }
+    }
+    ParamType::Bool => {
+      let v = U256::from_big_endian(word_at(data, at)?);
+      match v.bits() {
+        0 => Ok(Token::Bool(false)),
+        1 => Ok(Token::Bool(true)),
+        _ => Err(AbiError::InvalidData),
+      }
+    }
+    ParamType::Uint(bits) => {
+      let v = U256::from_big_endian(word_at(data, at)?);
+      match v.bits() <= *bits {
+        true => Ok(Token::Uint(v)),
+        false => Err(AbiError::InvalidData),
+      }
+    }
+    ParamType::Int(bits) => {
+      let v = U256::from_big_endian(word_at(data, at)?);
+      match is_sign_extended(v, *bits) {
+        true => Ok(Token::Int(v)),
+        false => Err(AbiError::InvalidData),
+      }
+    }
+    ParamType::FixedBytes(len) => {
+      Ok(Token::FixedBytes(word_at(data, at)?[..*len].to_vec()))
+    }
+    ParamType::Bytes => Ok(Token::Bytes(decode_bytes(data, at, budget)?)),
+    ParamType::String => String::from_utf8(decode_bytes(data, at, budget)?)
+      .map(Token::String)
+      .map_err(|_| AbiError::InvalidData),
+    ParamType::Array(element) => {
+      let len = usize_at(data, at)?;
+      // every element takes at least one word, this bounds the allocation
+      claim(budget, len)?;
+      let types = vec![element.as_ref().clone(); len];
+      Ok(Token::Array(decode_sequence(
+        &types,
+        data,
+        at + WORD,
+        budget,
+      )?))
+    }
+    ParamType::FixedArray(element, len) => {
+      if *len > data.len() / WORD {
+        return Err(AbiError::InvalidData);
+      }
+      let types = vec![element.as_ref().clone(); *len];
+      Ok(Token::FixedArray(decode_sequence(
+        &types, data, at, budget,
+      )?))
+    }
+    ParamType::Tuple(types) => {
+      Ok(Token::Tuple(decode_sequence(types, data, at, budget)?))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn words(hex_words: &[&str]) -> Vec<u8> {
+    hex::decode(hex_words.concat()).unwrap()
+  }
+
+  #[test]
+  fn selectors() {
+    assert_eq!(selector("transfer(address,uint256)"), [
+      0xa9, 0x05, 0x9c, 0xbb
+    ]);
+    assert_eq!(Function::parse("balanceOf(address)").unwrap().selector(), [
+      0x70, 0xa0, 0x82, 0x31
+    ]);
+    assert_eq!(
+      Function::parse("f(uint, (int,bytes32)[2])")
+        .unwrap()
+        .signature(),
+      "f(uint256,(int256,bytes32)[2])"
+    );
+  }
+
+  #[test]
+  fn parse_types() {
+    assert_eq!(ParamType::parse("uint8"), Ok(ParamType::Uint(8)));
+    assert_eq!(
+      ParamType::parse("(address,string)[]"),
+      Ok(ParamType::Array(Box::new(ParamType::Tuple(vec![
+        ParamType::Address,
+        ParamType::String
+      ]))))
+    );
+    assert_eq!(
+      ParamType::parse("bytes32[2][]"),
+      Ok(ParamType::Array(Box::new(ParamType::FixedArray(
+        Box::new(ParamType::FixedBytes(32)),
+        2
+      ))))
+    );
+    assert!(ParamType::parse("uint7").is_err());
+    assert!(ParamType::parse("bytes33").is_err());
+    assert!(ParamType::parse("(uint256").is_err());
+    assert!(ParamType::parse("mapping").is_err());
+  }
+
+  #[test]
+  fn static_and_dynamic_arguments() {
+    // example from the Solidity ABI specification
+    let function = Function::parse("f(uint256,uint32[],bytes10,bytes)").unwrap();
+    let args = vec![
+      Token::Uint(0x123.into()),
+      Token::Array(vec![Token::Uint(0x456.into()), Token::Uint(0x789.into())]),
+      Token::FixedBytes(b"1234567890".to_vec()),
+      Token::Bytes(b"Hello, world!".to_vec()),
+    ];
+
+    let encoded = function.encode_input(&args).unwrap();
+    let expected = words(&[
+      "8be65246",
+      "0000000000000000000000000000000000000000000000000000000000000123",
+      "0000000000000000000000000000000000000000000000000000000000000080",
+      "3132333435363738393000000000000000000000000000000000000000000000",
+      "00000000000000000000000000000000000000000000000000000000000000e0",
+      "0000000000000000000000000000000000000000000000000000000000000002",
+      "0000000000000000000000000000000000000000000000000000000000000456",
+      "0000000000000000000000000000000000000000000000000000000000000789",
+      "000000000000000000000000000000000000000000000000000000000000000d",
+      "48656c6c6f2c20776f726c642100000000000000000000000000000000000000",
+    ]);
+    assert_eq!(encoded, expected);
+    assert_eq!(function.decode_input(&encoded).unwrap(), args);
+  }
+
+  #[test]
+  fn nested_tuples_roundtrip() {
+    let types = vec![
+      ParamType::parse("(address,string,uint8[2])[]").unwrap(),
+      ParamType::Bool,
+      ParamType::parse("(uint256,bytes32)").unwrap(),
+    ];
+    let tokens = vec![
+      Token::Array(vec![
+        Token::Tuple(vec![
+          Token::Address(H160::repeat_byte(1)),
+          "one".into(),
+          Token::FixedArray(vec![Token::Uint(1.into()), Token::Uint(2.into())]),
+        ]),
+        Token::Tuple(vec![
+          Token::Address(H160::repeat_byte(2)),
+          "".into(),
+          Token::FixedArray(vec![Token::Uint(3.into()), Token::Uint(4.into())]),
+        ]),
+      ]),
+      true.into(),
+      Token::Tuple(vec![Token::Uint(U256::MAX), Token::FixedBytes(vec![7; 32])]),
+    ];
+
+    assert!(tokens.iter().zip(&types).all(|(t, p)| t.type_check(p)));
+    assert_eq!(decode(&types, &encode(&tokens)).unwrap(), tokens);
+  }
+
+  #[test]
+  fn malformed_data() {
+    let uint = [ParamType::Uint(256)];
+    assert_eq!(decode(&uint, &[0u8; 31]), Err(AbiError::InvalidData));
+
+    // offset pointing past the end of the data
+    let mut data = [0u8; 32];
+    data[31] = 0x40;
+    assert_eq!(
+      decode(&[ParamType::Bytes], &data),
+      Err(AbiError::InvalidData)
+    );
+
+    // huge array length
+    let data = words(&[
+      "0000000000000000000000000000000000000000000000000000000000000020",
+      "00000000000000000000000000000000000000000000000000000000ffffffff",
+    ]);
+    assert_eq!(
+      decode(&[ParamType::Array(Box::new(ParamType::Uint(256)))], &data),
+      Err(AbiError::InvalidData)
+    );
+
+    // dirty address and bool words
+    assert!(decode(&[ParamType::Address], &[0xff; 32]).is_err());
+    let two = words(&[&"00".repeat(31), "02"]);
+    assert!(decode(&[ParamType::Bool], &two).is_err());
+    let overflow = words(&[&"00".repeat(30), "0100"]);
+    assert!(decode(&[ParamType::Uint(8)], &overflow).is_err());
+  }
+
+  #[test]
+  fn signed_integer_width() {
+    let minus = |v: u64| !U256::from(v) + 1;
+    for (v, fits) in [
+      (U256::from(127), true),
+      (U256::from(128), false),
+      (minus(128), true),
+      (minus(129), false),
+      (U256::MAX, true),
+    ] {
+      assert_eq!(Token::Int(v).type_check(&ParamType::Int(8)), fits);
+      assert_eq!(
+        decode(&[ParamType::Int(8)], &uint_word(v)).is_ok(),
+        fits,
+        "{v:x}"
+      );
+    }
+    assert!(Token::Int(minus(129)).type_check(&ParamType::Int(256)));
+  }
+
+  #[test]
+  fn aliased_offsets_are_bounded() {
+    // four elements of a uint256[][] that all point to the same inner
+    // array, decoding them would claim more words than the data holds
+    let data = words(&[
+      "0000000000000000000000000000000000000000000000000000000000000020",
+      "0000000000000000000000000000000000000000000000000000000000000004",
+      "0000000000000000000000000000000000000000000000000000000000000080",
+      "0000000000000000000000000000000000000000000000000000000000000080",
+      "0000000000000000000000000000000000000000000000000000000000000080",
+      "0000000000000000000000000000000000000000000000000000000000000080",
+      "0000000000000000000000000000000000000000000000000000000000000004",
+      "0000000000000000000000000000000000000000000000000000000000000001",
+      "0000000000000000000000000000000000000000000000000000000000000002",
+      "0000000000000000000000000000000000000000000000000000000000000003",
+      "0000000000000000000000000000000000000000000000000000000000000004",
+    ]);
+    let nested = ParamType::parse("uint256[][]").unwrap();
+    assert_eq!(decode(&[nested], &data), Err(AbiError::InvalidData));
+  }
+}
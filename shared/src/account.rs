@@ -40,8 +40,7 @@ impl Default for AccountKind {
 ///
 /// If an account is an EOA it is optionally linked to a FileCoin account,
 /// in that case, the balance of that account is the sum of balances on the
-/// FVM side (if known) and EVM side, and the nonce value is the higher of
-/// the two. The mapping between FVM and EVM addresses is a manual step and
+/// FVM side (if known) and EVM side. The mapping between FVM and EVM addresses is a manual step and
 /// not all ETH addresses will have their FVM secp256k1 equivalent.
 ///
 /// If an account is a contract account, then it may be linked to an FVM
@@ -51,9 +50,11 @@ impl Default for AccountKind {
   Debug, Clone, Copy, Default, PartialEq, Serialize_tuple, Deserialize_tuple,
 )]
 pub struct EthereumAccount {
-  /// For EOA it could be this value of the FIL account nonce,
-  /// whichever is greater. If this account is not linked to a FIL
-  /// account, then this nonce value alone is used as the nonce.
+  /// The number of transactions sent by this account through the bridge.
+  ///
+  /// Actors cannot observe the sequence of a FIL account, so for linked
+  /// EOAs this is the EVM-side nonce alone, messages sent directly by the
+  /// FIL account do not advance it.
   pub nonce: u64,
 
   /// The FIL balance of this account.
@@ -68,3 +69,23 @@ pub struct EthereumAccount {
   /// EOA may optionally have a link to a FIL address.
   pub kind: AccountKind,
 }
+
+impl EthereumAccount {
+  /// True if the account has no nonce, no balance and is not linked to
+  /// anything, which makes it indistinguishable from an account that does
+  /// not exist (EIP-161).
+  pub fn is_empty(&self) -> bool {
+    self.nonce == 0 && self.balance.is_zero() && self.kind == AccountKind::default()
+  }
+
+  /// The FIL account whose balance counts towards this account's balance.
+  ///
+  /// Only EOAs linked to their FIL equivalent have one. The actor of a
+  /// contract account never holds any FIL on behalf of the contract.
+  pub fn linked_fil_account(&self) -> Option<FileCoinAddress> {
+    match self.kind {
+      AccountKind::ExternallyOwned { fil_account } => fil_account,
+      AccountKind::Contract { .. } => None,
+    }
+  }
+}
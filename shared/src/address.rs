@@ -0,0 +1,156 @@
+use {
+  crate::H160,
+  fvm_shared::{
+    address::{Address, Payload},
+    ActorID,
+  },
+};
+
+/// First byte of an EVM address that embeds an FVM actor ID.
+///
+/// The remaining bytes are eleven zero bytes followed by the big endian
+/// actor ID, so `f0100` is `0xff00000000000000000000000000000000000064`.
+pub const ID_ADDRESS_PREFIX: u8 = 0xff;
+
+/// Returns the EVM address that embeds the ID of an FVM actor.
+pub fn id_to_h160(id: ActorID) -> H160 {
+  let mut bytes = [0u8; 20];
+  bytes[0] = ID_ADDRESS_PREFIX;
+  bytes[12..].copy_from_slice(&id.to_be_bytes());
+  H160(bytes)
+}
+
+/// Returns the actor ID embedded in an EVM address, if any.
+pub fn h160_to_id(address: &H160) -> Option<ActorID> {
+  let bytes = address.as_bytes();
+  match bytes[0] == ID_ADDRESS_PREFIX && bytes[1..12].iter().all(|b| *b == 0) {
+    true => Some(ActorID::from_be_bytes(bytes[12..].try_into().unwrap())),
+    false => None,
+  }
+}
+
+/// Maps an EVM address to an FVM address without consulting any state.
+///
+/// Only addresses that embed an actor ID can be mapped this way, all other
+/// addresses have to be looked up in the bridge accounts map.
+pub fn h160_to_fil_address(address: &H160) -> Option<Address> {
+  h160_to_id(address).map(Address::new_id)
+}
+
+/// Maps an FVM address to an EVM address without consulting any state.
+///
+/// Only ID addresses can be mapped this way, robust addresses have to be
+/// resolved to their ID first.
+pub fn fil_address_to_h160(address: &Address) -> Option<H160> {
+  match address.payload() {
+    Payload::ID(id) => Some(id_to_h160(*id)),
+    _ => None,
+  }
+}
+
+/// Address of an actor created from the EVM world.
+///
+/// It is composed of the ID of the actor managing the namespace (the bridge)
+/// and the EVM address of the created actor. The byte representation is the
+/// LEB128 encoded namespace followed by the 20 bytes of the EVM address, the
+/// payload layout of delegated (f4) Filecoin addresses, so both sides can
+/// derive one from the other without a lookup.
+///
+/// The FVM this crate builds against has no delegated address protocol
+/// yet, so these addresses cannot be resolved to actors and lookups of
+/// contract addresses still go through the bridge accounts map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DelegatedAddress {
+  pub namespace: ActorID,
+  pub subaddress: H160,
+}
+
+impl DelegatedAddress {
+  pub fn new(namespace: ActorID, subaddress: H160) -> Self {
+    Self {
+      namespace,
+      subaddress,
+    }
+  }
+
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(30);
+    let mut namespace = self.namespace;
+    loop {
+      let byte = (namespace & 0x7f) as u8;
+      namespace >>= 7;
+      if namespace == 0 {
+        bytes.push(byte);
+        break;
+      }
+      bytes.push(byte | 0x80);
+    }
+    bytes.extend_from_slice(self.subaddress.as_bytes());
+    bytes
+  }
+}
+
+impl TryFrom<&[u8]> for DelegatedAddress {
+  type Error = &'static str;
+
+  fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+    let mut namespace: ActorID = 0;
+    for (i, byte) in bytes.iter().enumerate().take(10) {
+      namespace |= ActorID::from(byte & 0x7f) << (7 * i);
+      if byte & 0x80 == 0 {
+        let subaddress = &bytes[i + 1..];
+        if subaddress.len() != 20 {
+          return Err("invalid delegated subaddress length");
+        }
+        return Ok(Self::new(namespace, H160::from_slice(subaddress)));
+      }
+    }
+    Err("invalid delegated address namespace")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn id_address_roundtrip() {
+    let address = id_to_h160(100);
+    assert_eq!(
+      address,
+      H160::from_slice(&hex_literal::hex!(
+        "ff00000000000000000000000000000000000064"
+      ))
+    );
+    assert_eq!(h160_to_id(&address), Some(100));
+    assert_eq!(h160_to_fil_address(&address), Some(Address::new_id(100)));
+    assert_eq!(fil_address_to_h160(&Address::new_id(100)), Some(address));
+    assert_eq!(h160_to_id(&id_to_h160(u64::MAX)), Some(u64::MAX));
+  }
+
+  #[test]
+  fn non_id_addresses() {
+    assert_eq!(h160_to_id(&H160::zero()), None);
+    assert_eq!(h160_to_id(&H160::repeat_byte(0xff)), None);
+
+    let mut address = id_to_h160(1);
+    address.0[5] = 1;
+    assert_eq!(h160_to_fil_address(&address), None);
+  }
+
+  #[test]
+  fn delegated_address_roundtrip() {
+    for namespace in [0, 10, 127, 128, 10002, u64::MAX] {
+      let address = DelegatedAddress::new(namespace, H160::repeat_byte(0xab));
+      let bytes = address.to_bytes();
+      assert_eq!(DelegatedAddress::try_from(bytes.as_slice()), Ok(address));
+    }
+
+    assert_eq!(
+      DelegatedAddress::new(10002, H160::zero()).to_bytes()[..2],
+      [0x92, 0x4e]
+    );
+    assert!(DelegatedAddress::try_from(&[10u8, 1, 2][..]).is_err());
+    assert!(DelegatedAddress::try_from(&[0x80u8; 11][..]).is_err());
+  }
+}
@@ -3,6 +3,45 @@ use {
   std::ops::Deref,
 };
 
+/// Maximum size of the code of a deployed contract (EIP-170).
+pub const MAX_CODE_SIZE: usize = 0x6000;
+
+/// Maximum size of contract initialization code (EIP-3860).
+pub const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+
+/// Gas charged for every byte of code returned by a contract constructor.
+pub const CODE_DEPOSIT_COST: i64 = 200;
+
+/// Gas charged for every 32-byte word of initialization code (EIP-3860).
+pub const INITCODE_WORD_COST: u64 = 2;
+
+/// Checks that contract initialization code is within the size
+/// limit introduced in EIP-3860.
+pub fn validate_initcode(initcode: &[u8]) -> Result<(), StatusCode> {
+  if initcode.len() > MAX_INITCODE_SIZE {
+    return Err(StatusCode::ContractValidationFailure);
+  }
+  Ok(())
+}
+
+/// Validates the code returned by a contract constructor and charges
+/// the code deposit cost for it from `gas_left`.
+///
+/// Code larger than the EIP-170 limit or starting with the 0xEF byte
+/// reserved by EIP-3541 is rejected.
+pub fn deposit_code(code: &[u8], gas_left: &mut i64) -> Result<(), StatusCode> {
+  if code.len() > MAX_CODE_SIZE || code.first() == Some(&0xef) {
+    return Err(StatusCode::ContractValidationFailure);
+  }
+
+  *gas_left -= CODE_DEPOSIT_COST * code.len() as i64;
+  if *gas_left < 0 {
+    return Err(StatusCode::OutOfGas);
+  }
+
+  Ok(())
+}
+
 pub struct Bytecode<'c> {
   code: &'c [u8],
   jumpdest: Vec<bool>,
@@ -54,3 +93,40 @@ impl<'c> AsRef<[u8]> for Bytecode<'c> {
     self.code
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn code_deposit() {
+    let mut gas_left = 1000;
+    assert_eq!(Ok(()), deposit_code(&[0x60, 0x00], &mut gas_left));
+    assert_eq!(600, gas_left);
+
+    assert_eq!(
+      Err(StatusCode::OutOfGas),
+      deposit_code(&[0; 4], &mut gas_left)
+    );
+
+    let mut gas_left = i64::MAX;
+    assert_eq!(
+      Err(StatusCode::ContractValidationFailure),
+      deposit_code(&[0xef, 0x00], &mut gas_left)
+    );
+    assert_eq!(
+      Err(StatusCode::ContractValidationFailure),
+      deposit_code(&vec![0; MAX_CODE_SIZE + 1], &mut gas_left)
+    );
+    assert_eq!(Ok(()), deposit_code(&vec![0; MAX_CODE_SIZE], &mut gas_left));
+  }
+
+  #[test]
+  fn initcode_size() {
+    assert_eq!(Ok(()), validate_initcode(&vec![0; MAX_INITCODE_SIZE]));
+    assert_eq!(
+      Err(StatusCode::ContractValidationFailure),
+      validate_initcode(&vec![0; MAX_INITCODE_SIZE + 1])
+    );
+  }
+}
@@ -1,6 +1,7 @@
 use {
   crate::{
     bytecode::Bytecode,
+    host::Host,
     instructions::{
       arithmetic,
       bitwise,
@@ -11,25 +12,26 @@ use {
       hash,
       log::log,
       memory,
-      stack::{self, dup, push, push1, push32, swap},
+      stack::{self, dup, push, push0, push1, push32, swap},
       storage,
     },
     memory::Memory,
     message::{CallKind, Message},
     opcode::OpCode,
     output::StatusCode,
-    stack::Stack,
-    system::System,
+    stack::{Stack, MAX_STACK_SIZE},
     Output,
   },
   bytes::Bytes,
-  fvm_ipld_blockstore::Blockstore,
 };
 
 /// EVM execution runtime.
 #[derive(Clone, Debug)]
 pub struct ExecutionState<'m> {
   pub gas_left: i64,
+  /// Refund counter (EIP-2200), may be negative until the end of the
+  /// transaction.
+  pub gas_refund: i64,
   pub stack: Stack,
   pub memory: Memory,
   pub message: &'m Message,
@@ -41,6 +43,7 @@ impl<'m> ExecutionState<'m> {
   pub fn new(message: &'m Message) -> Self {
     Self {
       gas_left: message.gas,
+      gas_refund: 0,
       stack: Stack::default(),
       memory: Memory::default(),
       message,
@@ -50,20 +53,36 @@ impl<'m> ExecutionState<'m> {
   }
 }
 
-pub fn execute<'r, BS: Blockstore>(
+pub fn execute<H: Host>(
   bytecode: &Bytecode,
   runtime: &mut ExecutionState,
-  system: &'r System<'r, BS>,
+  system: &H,
 ) -> Result<Output, StatusCode> {
   let mut pc = 0; // program counter
   let mut reverted = false;
+  let revision = system.transaction_context().revision;
 
   loop {
     if pc >= bytecode.len() {
       break;
     }
 
-    let op = OpCode::try_from(bytecode[pc])?;
+    let op = OpCode::lookup(bytecode[pc], revision)?;
+
+    // instructions assume that all the stack items they access exist
+    let height = runtime.stack.len();
+    if height < usize::from(op.stack_height_required) {
+      return Err(StatusCode::StackUnderflow);
+    }
+    if height as isize + isize::from(op.stack_height_change) > MAX_STACK_SIZE as isize {
+      return Err(StatusCode::StackOverflow);
+    }
+
+    runtime.gas_left -= i64::from(op.price);
+    if runtime.gas_left < 0 {
+      return Err(StatusCode::OutOfGas);
+    }
+
     match op {
       OpCode::STOP => break,
       OpCode::ADD => arithmetic::add(&mut runtime.stack),
@@ -121,6 +140,7 @@ pub fn execute<'r, BS: Blockstore>(
       OpCode::MLOAD => memory::mload(runtime)?,
       OpCode::MSTORE => memory::mstore(runtime)?,
       OpCode::MSTORE8 => memory::mstore8(runtime)?,
+      OpCode::MCOPY => memory::mcopy(runtime)?,
       OpCode::JUMP => {
         pc = control::jump(&mut runtime.stack, &bytecode)?;
         continue; // don't increment PC after the jump
@@ -136,9 +156,12 @@ pub fn execute<'r, BS: Blockstore>(
       OpCode::MSIZE => memory::msize(runtime),
       OpCode::SLOAD => storage::sload(runtime, system)?,
       OpCode::SSTORE => storage::sstore(runtime, system)?,
+      OpCode::TLOAD => storage::tload(runtime, system),
+      OpCode::TSTORE => storage::tstore(runtime, system)?,
       OpCode::GAS => control::gas(runtime),
       OpCode::JUMPDEST => {} // marker opcode for valid jumps addresses
-      OpCode::PUSH1 => pc += push1(&mut runtime.stack, bytecode[pc + 1]),
+      OpCode::PUSH0 => push0(&mut runtime.stack),
+      OpCode::PUSH1 => pc += push1(&mut runtime.stack, &bytecode[pc + 1..]),
       OpCode::PUSH2 => pc += push::<2>(&mut runtime.stack, &bytecode[pc + 1..]),
       OpCode::PUSH3 => pc += push::<3>(&mut runtime.stack, &bytecode[pc + 1..]),
       OpCode::PUSH4 => pc += push::<4>(&mut runtime.stack, &bytecode[pc + 1..]),
@@ -219,17 +242,29 @@ pub fn execute<'r, BS: Blockstore>(
         break;
       }
       OpCode::INVALID => return Err(StatusCode::InvalidInstruction),
-      OpCode::SELFDESTRUCT => storage::selfdestruct(runtime, system)?,
+      OpCode::SELFDESTRUCT => {
+        storage::selfdestruct(runtime, system)?;
+        break; // selfdestruct halts execution
+      }
       _ => return Err(StatusCode::UndefinedInstruction),
     }
 
     pc += 1; // advance
   }
 
+  // refunds are only paid if the state changes persist. The cap depends
+  // on the gas used by the whole transaction, including intrinsic gas,
+  // so it is applied by the caller (see `Revision::capped_refund`).
+  let gas_refund = match reverted {
+    true => 0,
+    false => runtime.gas_refund,
+  };
+
   Ok(Output {
     reverted,
     status_code: StatusCode::Success,
     gas_left: runtime.gas_left,
+    gas_refund,
     output_data: runtime.output_data.clone(),
   })
 }
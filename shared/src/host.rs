@@ -0,0 +1,218 @@
+use {
+  crate::{message::Message, output::StatusCode, revision::Revision, Output, H160, U256},
+  bytes::Bytes,
+  serde_tuple::{Deserialize_tuple, Serialize_tuple},
+};
+
+/// Info sourced from the current transaction and block
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct TransactionContext {
+  /// The transaction gas price.
+  pub tx_gas_price: U256,
+  /// The transaction origin account.
+  pub tx_origin: H160,
+  /// The miner of the block.
+  pub block_coinbase: H160,
+  /// The block number.
+  pub block_number: u64,
+  /// The block timestamp.
+  pub block_timestamp: u64,
+  /// The block gas limit.
+  pub block_gas_limit: u64,
+  /// The block difficulty, PREVRANDAO since the merge (EIP-4399).
+  pub block_difficulty: U256,
+  /// The blockchain's ChainID.
+  pub chain_id: U256,
+  /// The block base fee per gas (EIP-1559, EIP-3198).
+  pub block_base_fee: U256,
+  /// The hard fork whose rules the transaction is executed under.
+  pub revision: Revision,
+}
+
+/// A log record emitted by LOG0..LOG4.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct Log {
+  /// The account that emitted the log.
+  pub address: H160,
+  /// The log data, read from memory.
+  pub data: Bytes,
+  /// Up to four topics, in the order they were popped from the stack.
+  pub topics: Vec<U256>,
+}
+
+/// State access status (EIP-2929).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessStatus {
+  Cold,
+  Warm,
+}
+
+impl Default for AccessStatus {
+  fn default() -> Self {
+    Self::Cold
+  }
+}
+
+/// The effect of writing a storage item (EIP-2200).
+///
+/// Transitions read as `original -> current -> new`, where the original
+/// value is the one the item had at the beginning of the transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageStatus {
+  /// The value of a storage item has been left unchanged: 0 -> 0 and X -> X.
+  Unchanged,
+  /// The value of a storage item has been modified: X -> X -> Z.
+  Modified,
+  /// A storage item has been modified after being modified before: X -> Y -> Z.
+  ModifiedAgain,
+  /// A new storage item has been added: 0 -> 0 -> Z.
+  Added,
+  /// A storage item has been deleted: X -> X -> 0.
+  Deleted,
+  /// A deleted storage item has been added back: X -> 0 -> Z.
+  DeletedAdded,
+  /// A modified storage item has been deleted: X -> Y -> 0.
+  ModifiedDeleted,
+  /// A deleted storage item has been restored: X -> 0 -> X.
+  DeletedRestored,
+  /// An added storage item has been deleted: 0 -> Y -> 0.
+  AddedDeleted,
+  /// A modified storage item has been restored: X -> Y -> X.
+  ModifiedRestored,
+}
+
+impl StorageStatus {
+  /// Classifies writing `new` to an item that currently holds `current`
+  /// and held `original` at the beginning of the transaction.
+  pub fn new(original: U256, current: U256, new: U256) -> Self {
+    if current == new {
+      Self::Unchanged
+    } else if original == current {
+      match (original.is_zero(), new.is_zero()) {
+        (true, _) => Self::Added,
+        (false, true) => Self::Deleted,
+        (false, false) => Self::Modified,
+      }
+    } else if original.is_zero() {
+      match new.is_zero() {
+        true => Self::AddedDeleted,
+        false => Self::ModifiedAgain,
+      }
+    } else if current.is_zero() {
+      match new == original {
+        true => Self::DeletedRestored,
+        false => Self::DeletedAdded,
+      }
+    } else if new.is_zero() {
+      Self::ModifiedDeleted
+    } else if new == original {
+      Self::ModifiedRestored
+    } else {
+      Self::ModifiedAgain
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Call<'a> {
+  Call(&'a Message),
+  Create(&'a Message),
+}
+
+/// The interface through which the interpreter observes and modifies the
+/// world state, modelled after the EVMC host interface.
+///
+/// [`execute`](crate::execute) and all instructions are generic over this
+/// trait. On chain it is implemented by [`System`](crate::System), which
+/// reaches the FVM through syscalls and the bridge actor, while tests and
+/// tools can provide their own implementation that runs natively.
+pub trait Host {
+  /// Check if an account exists.
+  ///
+  /// Empty accounts are considered non-existent (EIP-161).
+  fn account_exists(&self, address: H160) -> Result<bool, StatusCode>;
+
+  /// Get value of a storage key.
+  ///
+  /// Returns `Ok(U256::zero())` if does not exist.
+  fn get_storage(&self, address: H160, key: U256) -> Result<U256, StatusCode>;
+
+  /// Set value of a storage key.
+  ///
+  /// The returned status depends on the value the key had at the
+  /// beginning of the transaction, see [`StorageStatus::new`].
+  fn set_storage(
+    &self,
+    address: H160,
+    key: U256,
+    value: U256,
+  ) -> Result<StorageStatus, StatusCode>;
+
+  /// Get value of a transient storage key (EIP-1153).
+  ///
+  /// Returns `U256::zero()` if does not exist.
+  fn get_transient_storage(&self, address: H160, key: U256) -> U256;
+
+  /// Set value of a transient storage key (EIP-1153).
+  ///
+  /// Transient storage is discarded at the end of the transaction, and
+  /// writes made by a call frame are reverted if that frame fails.
+  fn set_transient_storage(&self, address: H160, key: U256, value: U256);
+
+  /// Get balance of an account.
+  ///
+  /// Returns `Ok(0)` if account does not exist.
+  fn get_balance(&self, address: H160) -> Result<U256, StatusCode>;
+
+  /// Get code size of an account.
+  ///
+  /// Returns `Ok(0)` if account does not exist.
+  fn get_code_size(&self, address: H160) -> Result<U256, StatusCode>;
+
+  /// Get code hash of an account.
+  ///
+  /// Returns `Ok(0)` if account does not exist.
+  fn get_code_hash(&self, address: H160) -> Result<U256, StatusCode>;
+
+  /// Copy code of an account.
+  ///
+  /// Returns `Ok(0)` if offset is invalid.
+  fn copy_code(
+    &self,
+    address: H160,
+    offset: usize,
+    buffer: &mut [u8],
+  ) -> Result<usize, StatusCode>;
+
+  /// Self-destruct account.
+  fn selfdestruct(&self, address: H160, beneficiary: H160) -> Result<(), StatusCode>;
+
+  /// Call to another account.
+  fn call(&self, msg: Call) -> Output;
+
+  /// Get block hash.
+  ///
+  /// Returns `U256::zero()` if block does not exist.
+  fn get_block_hash(&self, block_number: u64) -> U256;
+
+  /// Emit a log.
+  fn emit_log(
+    &self,
+    address: H160,
+    data: Bytes,
+    topics: &[U256],
+  ) -> Result<(), StatusCode>;
+
+  /// Mark account as warm, return previous access status.
+  ///
+  /// Returns `AccessStatus::Cold` if account does not exist.
+  fn access_account(&self, address: H160) -> AccessStatus;
+
+  /// Mark storage key as warm, return previous access status.
+  ///
+  /// Returns `AccessStatus::Cold` if account does not exist.
+  fn access_storage(&self, address: H160, key: U256) -> AccessStatus;
+
+  /// Return context information about the current transaction and current block
+  fn transaction_context(&self) -> &TransactionContext;
+}
@@ -2,13 +2,12 @@ use {
   super::memory::{get_memory_region, num_words},
   crate::{
     execution::ExecutionState,
+    host::Host,
     message::CallKind,
     output::StatusCode,
     stack::Stack,
-    system::System,
     U256,
   },
-  fvm_ipld_blockstore::Blockstore,
 };
 
 #[inline]
@@ -113,11 +112,13 @@ pub fn codecopy(state: &mut ExecutionState, code: &[u8]) -> Result<(), StatusCod
 }
 
 #[inline]
-pub fn call<'r, BS: Blockstore>(
+pub fn call<H: Host>(
   _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+  _platform: &H,
   _kind: CallKind,
   _is_static: bool,
 ) -> Result<(), StatusCode> {
-  todo!();
+  Err(StatusCode::InternalError(
+    "CALL is not supported yet".into(),
+  ))
 }
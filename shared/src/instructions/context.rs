@@ -1,115 +1,139 @@
-use {
-  crate::{
-    execution::ExecutionState,
-    output::StatusCode,
-    system::System,
-    uints::address_to_u256,
-  },
-  fvm_ipld_blockstore::Blockstore,
+use crate::{
+  execution::ExecutionState,
+  host::Host,
+  output::StatusCode,
+  uints::address_to_u256,
+  U256,
 };
 
 #[inline]
-pub fn blockhash<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn blockhash<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  let number = state.stack.pop();
+
+  let hash = match u64::try_from(number) {
+    Ok(number) => platform.get_block_hash(number),
+    Err(_) => U256::zero(),
+  };
+
+  state.stack.push(hash);
+  Ok(())
 }
 
 #[inline]
-pub fn caller<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn caller<H: Host>(
+  state: &mut ExecutionState,
+  _platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  state.stack.push(address_to_u256(state.message.sender));
+  Ok(())
 }
 
 #[inline]
-pub fn call_value<'r, BS: Blockstore>(
-  state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
-) {
+pub fn call_value<H: Host>(state: &mut ExecutionState, _platform: &H) {
   state.stack.push(state.message.value);
 }
 
 #[inline]
-pub fn address<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn address<H: Host>(
+  state: &mut ExecutionState,
+  _platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  state.stack.push(address_to_u256(state.message.recipient));
+  Ok(())
 }
 
 #[inline]
-pub fn origin<'r, BS: Blockstore>(
-  state: &mut ExecutionState,
-  platform: &'r System<'r, BS>,
-) {
+pub fn origin<H: Host>(state: &mut ExecutionState, platform: &H) {
   state
     .stack
     .push(address_to_u256(platform.transaction_context().tx_origin))
 }
 
 #[inline]
-pub fn coinbase<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn coinbase<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  state.stack.push(address_to_u256(
+    platform.transaction_context().block_coinbase,
+  ));
+  Ok(())
 }
 
 #[inline]
-pub fn gas_price<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn gas_price<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  state
+    .stack
+    .push(platform.transaction_context().tx_gas_price);
+  Ok(())
 }
 
 #[inline]
-pub fn timestamp<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn timestamp<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  state
+    .stack
+    .push(platform.transaction_context().block_timestamp.into());
+  Ok(())
 }
 
 #[inline]
-pub fn block_number<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn block_number<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  state
+    .stack
+    .push(platform.transaction_context().block_number.into());
+  Ok(())
 }
 
 #[inline]
-pub fn difficulty<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn difficulty<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  state
+    .stack
+    .push(platform.transaction_context().block_difficulty);
+  Ok(())
 }
 
 #[inline]
-pub fn gas_limit<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn gas_limit<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  state
+    .stack
+    .push(platform.transaction_context().block_gas_limit.into());
+  Ok(())
 }
 
 #[inline]
-pub fn chain_id<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn chain_id<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  state.stack.push(platform.transaction_context().chain_id);
+  Ok(())
 }
 
 #[inline]
-pub fn base_fee<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn base_fee<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  state
+    .stack
+    .push(platform.transaction_context().block_base_fee);
+  Ok(())
 }
@@ -74,7 +74,11 @@ pub fn pc(stack: &mut Stack, pc: usize) {
 
 #[inline]
 pub fn jump(stack: &mut Stack, bytecode: &Bytecode) -> Result<usize, StatusCode> {
-  let dst = stack.pop().as_usize();
+  let dst = stack.pop();
+  if dst > U256::from(bytecode.len()) {
+    return Err(StatusCode::BadJumpDestination);
+  }
+  let dst = dst.as_usize();
   if !bytecode.valid_jump_destination(dst) {
     return Err(StatusCode::BadJumpDestination);
   }
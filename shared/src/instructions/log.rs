@@ -1,13 +1,43 @@
 use {
-  crate::{execution::ExecutionState, output::StatusCode, system::System},
-  fvm_ipld_blockstore::Blockstore,
+  super::memory::get_memory_region,
+  crate::{execution::ExecutionState, host::Host, output::StatusCode, U256},
+  arrayvec::ArrayVec,
+  bytes::Bytes,
 };
 
+/// Gas charged for every byte of log data. The cost of the topics is
+/// part of the price of each LOG instruction.
+const LOG_DATA_COST: i64 = 8;
+
 #[inline]
-pub fn log<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
-  _num_topics: usize,
+pub fn log<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
+  num_topics: usize,
 ) -> Result<(), StatusCode> {
-  todo!()
+  if state.message.is_static {
+    return Err(StatusCode::StaticModeViolation);
+  }
+
+  let offset = state.stack.pop();
+  let size = state.stack.pop();
+
+  let region =
+    get_memory_region(state, offset, size).map_err(|_| StatusCode::OutOfGas)?;
+
+  let data = match region {
+    Some(region) => {
+      state.gas_left -= LOG_DATA_COST * region.size.get() as i64;
+      if state.gas_left < 0 {
+        return Err(StatusCode::OutOfGas);
+      }
+      Bytes::copy_from_slice(
+        &state.memory[region.offset..region.offset + region.size.get()],
+      )
+    }
+    None => Bytes::new(),
+  };
+
+  let topics: ArrayVec<U256, 4> = (0..num_topics).map(|_| state.stack.pop()).collect();
+  platform.emit_log(state.message.recipient, data, &topics)
 }
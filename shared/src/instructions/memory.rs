@@ -1,6 +1,5 @@
 use {
-  crate::{execution::ExecutionState, output::StatusCode, system::System, U256},
-  fvm_ipld_blockstore::Blockstore,
+  crate::{execution::ExecutionState, host::Host, output::StatusCode, U256},
   std::num::NonZeroUsize,
 };
 
@@ -123,6 +122,35 @@ pub fn mstore8(state: &mut ExecutionState) -> Result<(), StatusCode> {
   Ok(())
 }
 
+/// Copies memory within memory (EIP-5656). Overlapping regions are copied
+/// as if through an intermediate buffer.
+#[inline]
+pub fn mcopy(state: &mut ExecutionState) -> Result<(), StatusCode> {
+  let dst = state.stack.pop();
+  let src = state.stack.pop();
+  let size = state.stack.pop();
+
+  // memory is expanded to cover both regions
+  let dst_region =
+    get_memory_region(state, dst, size).map_err(|_| StatusCode::OutOfGas)?;
+  let src_region =
+    get_memory_region(state, src, size).map_err(|_| StatusCode::OutOfGas)?;
+
+  if let (Some(dst), Some(src)) = (dst_region, src_region) {
+    let copy_cost = num_words(src.size.get()) * 3;
+    state.gas_left -= copy_cost as i64;
+    if state.gas_left < 0 {
+      return Err(StatusCode::OutOfGas);
+    }
+
+    state
+      .memory
+      .copy_within(src.offset..src.offset + src.size.get(), dst.offset);
+  }
+
+  Ok(())
+}
+
 #[inline]
 pub fn msize(state: &mut ExecutionState) {
   state
@@ -130,9 +158,11 @@ pub fn msize(state: &mut ExecutionState) {
     .push(u64::try_from(state.memory.len()).unwrap().into());
 }
 
-pub fn extcodecopy<'r, BS: Blockstore>(
+pub fn extcodecopy<H: Host>(
   _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+  _platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!();
+  Err(StatusCode::InternalError(
+    "EXTCODECOPY is not supported yet".into(),
+  ))
 }
@@ -1,21 +1,30 @@
 use crate::{stack::Stack, U256};
 
+/// Pushes the `LEN` bytes following the instruction. Push data that is
+/// cut off by the end of the code reads as zeros.
 #[inline]
 pub(crate) fn push<const LEN: usize>(stack: &mut Stack, code: &[u8]) -> usize {
-  stack.push(U256::from_big_endian(&code[..LEN]));
+  let mut data = [0u8; LEN];
+  let len = LEN.min(code.len());
+  data[..len].copy_from_slice(&code[..len]);
+  stack.push(U256::from_big_endian(&data));
   LEN
 }
 
 #[inline]
-pub(crate) fn push1(stack: &mut Stack, v: u8) -> usize {
-  stack.push(v.into());
+pub(crate) fn push0(stack: &mut Stack) {
+  stack.push(U256::zero());
+}
+
+#[inline]
+pub(crate) fn push1(stack: &mut Stack, code: &[u8]) -> usize {
+  stack.push(code.first().copied().unwrap_or_default().into());
   1
 }
 
 #[inline]
 pub(crate) fn push32(stack: &mut Stack, code: &[u8]) -> usize {
-  stack.push(U256::from_big_endian(&code[0..32]));
-  32
+  push::<32>(stack, code)
 }
 
 #[inline]
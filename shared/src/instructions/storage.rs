@@ -1,15 +1,20 @@
-use {
-  crate::{
-    execution::ExecutionState,
-    output::StatusCode,
-    system::{AccessStatus, StorageStatus, System},
-  },
-  fvm_ipld_blockstore::Blockstore,
+use crate::{
+  execution::ExecutionState,
+  host::{AccessStatus, Host, StorageStatus},
+  output::StatusCode,
+  uints::u256_to_address,
 };
 
 pub(crate) const COLD_SLOAD_COST: u16 = 2100;
-pub(crate) const _COLD_ACCOUNT_ACCESS_COST: u16 = 2600;
+pub(crate) const ADDITIONAL_COLD_SLOAD_COST: u16 =
+  COLD_SLOAD_COST - WARM_STORAGE_READ_COST;
+pub(crate) const COLD_ACCOUNT_ACCESS_COST: u16 = 2600;
+pub(crate) const ADDITIONAL_COLD_ACCOUNT_ACCESS_COST: u16 =
+  COLD_ACCOUNT_ACCESS_COST - WARM_STORAGE_READ_COST;
 pub(crate) const WARM_STORAGE_READ_COST: u16 = 100;
+pub(crate) const SELFDESTRUCT_NEW_ACCOUNT_COST: u16 = 25000;
+pub(crate) const SSTORE_SET_COST: u16 = 20000;
+pub(crate) const SSTORE_RESET_COST: u16 = 5000;
 
 #[inline(always)]
 fn ok_or_out_of_gas(gas_left: i64) -> Result<(), StatusCode> {
@@ -20,17 +25,27 @@ fn ok_or_out_of_gas(gas_left: i64) -> Result<(), StatusCode> {
 }
 
 #[inline]
-pub fn sload<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn sload<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!();
+  let location = state.stack.pop();
+
+  if platform.access_storage(state.message.recipient, location) == AccessStatus::Cold {
+    state.gas_left -= i64::from(ADDITIONAL_COLD_SLOAD_COST);
+    ok_or_out_of_gas(state.gas_left)?;
+  }
+
+  state
+    .stack
+    .push(platform.get_storage(state.message.recipient, location)?);
+  Ok(())
 }
 
 #[inline]
-pub fn sstore<'r, BS: Blockstore>(
+pub fn sstore<H: Host>(
   state: &mut ExecutionState,
-  platform: &'r System<'r, BS>,
+  platform: &H,
 ) -> Result<(), StatusCode> {
   if state.message.is_static {
     return Err(StatusCode::StaticModeViolation);
@@ -48,62 +63,143 @@ pub fn sstore<'r, BS: Blockstore>(
     cost = COLD_SLOAD_COST;
   }
 
-  cost = match platform.set_storage(state.message.recipient, location, value)? {
-    StorageStatus::Unchanged | StorageStatus::ModifiedAgain => {
-      cost + WARM_STORAGE_READ_COST
+  let status = platform.set_storage(state.message.recipient, location, value)?;
+  cost += match status {
+    StorageStatus::Added => SSTORE_SET_COST,
+    StorageStatus::Modified | StorageStatus::Deleted => {
+      SSTORE_RESET_COST - COLD_SLOAD_COST
     }
-    StorageStatus::Modified | StorageStatus::Deleted => cost + 5000 - COLD_SLOAD_COST,
-    StorageStatus::Added => cost + 20000,
+    _ => WARM_STORAGE_READ_COST,
+  };
+
+  // net gas metering, restoring an item refunds what writing it cost
+  // beyond a warm read (EIP-2200, EIP-3529)
+  let clears_refund = platform
+    .transaction_context()
+    .revision
+    .sstore_clears_refund();
+  let restored_refund =
+    i64::from(SSTORE_RESET_COST - COLD_SLOAD_COST - WARM_STORAGE_READ_COST);
+  state.gas_refund += match status {
+    StorageStatus::Deleted | StorageStatus::ModifiedDeleted => clears_refund,
+    StorageStatus::DeletedAdded => -clears_refund,
+    StorageStatus::DeletedRestored => restored_refund - clears_refund,
+    StorageStatus::AddedDeleted => i64::from(SSTORE_SET_COST - WARM_STORAGE_READ_COST),
+    StorageStatus::ModifiedRestored => restored_refund,
+    _ => 0,
   };
 
   state.gas_left -= i64::from(cost);
   ok_or_out_of_gas(state.gas_left)
 }
 
+/// Reads a transient storage slot of the current account (EIP-1153).
 #[inline]
-pub fn balance<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn tload<H: Host>(state: &mut ExecutionState, platform: &H) {
+  let location = state.stack.pop();
+  state
+    .stack
+    .push(platform.get_transient_storage(state.message.recipient, location));
+}
+
+/// Writes a transient storage slot of the current account (EIP-1153).
+#[inline]
+pub fn tstore<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  if state.message.is_static {
+    return Err(StatusCode::StaticModeViolation);
+  }
+
+  let location = state.stack.pop();
+  let value = state.stack.pop();
+  platform.set_transient_storage(state.message.recipient, location, value);
+  Ok(())
 }
 
 #[inline]
-pub fn selfbalance<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn balance<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  let address = u256_to_address(state.stack.pop());
+
+  if platform.access_account(address) == AccessStatus::Cold {
+    state.gas_left -= i64::from(ADDITIONAL_COLD_ACCOUNT_ACCESS_COST);
+    ok_or_out_of_gas(state.gas_left)?;
+  }
+
+  state.stack.push(platform.get_balance(address)?);
+  Ok(())
 }
 
 #[inline]
-pub fn extcodesize<'r, BS: Blockstore>(
+pub fn selfbalance<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
+) -> Result<(), StatusCode> {
+  let balance = platform.get_balance(state.message.recipient)?;
+  state.stack.push(balance);
+  Ok(())
+}
+
+#[inline]
+pub fn extcodesize<H: Host>(
   _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+  _platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  Err(StatusCode::InternalError(
+    "EXTCODESIZE is not supported yet".into(),
+  ))
 }
 
-pub fn extcodehash<'r, BS: Blockstore>(
+pub fn extcodehash<H: Host>(
   _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+  _platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!();
+  Err(StatusCode::InternalError(
+    "EXTCODEHASH is not supported yet".into(),
+  ))
 }
 
 #[inline]
-pub fn create<'r, BS: Blockstore>(
+pub fn create<H: Host>(
   _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+  _platform: &H,
   _create2: bool,
 ) -> Result<(), StatusCode> {
-  todo!()
+  Err(StatusCode::InternalError(
+    "CREATE is not supported yet".into(),
+  ))
 }
 
 #[inline]
-pub fn selfdestruct<'r, BS: Blockstore>(
-  _state: &mut ExecutionState,
-  _platform: &'r System<'r, BS>,
+pub fn selfdestruct<H: Host>(
+  state: &mut ExecutionState,
+  platform: &H,
 ) -> Result<(), StatusCode> {
-  todo!()
+  if state.message.is_static {
+    return Err(StatusCode::StaticModeViolation);
+  }
+
+  let beneficiary = u256_to_address(state.stack.pop());
+
+  let mut cost = 0;
+  if platform.access_account(beneficiary) == AccessStatus::Cold {
+    cost += i64::from(COLD_ACCOUNT_ACCESS_COST);
+  }
+
+  // transferring a non-zero balance to an account
+  // that does not exist yet brings it into existence.
+  if !platform.get_balance(state.message.recipient)?.is_zero()
+    && !platform.account_exists(beneficiary)?
+  {
+    cost += i64::from(SELFDESTRUCT_NEW_ACCOUNT_COST);
+  }
+
+  state.gas_left -= cost;
+  ok_or_out_of_gas(state.gas_left)?;
+
+  platform.selfdestruct(state.message.recipient, beneficiary)
 }
@@ -1,33 +1,82 @@
 //! Shared types between the EVM address registry and EVM runtime actors
 
+pub mod abi;
 mod account;
+mod address;
 mod bytecode;
 mod execution;
+mod host;
 mod instructions;
 mod memory;
 mod message;
 mod opcode;
 mod output;
+mod revision;
 mod stack;
 mod system;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 mod transaction;
 pub mod uints;
 
 pub use {
   account::{AccountKind, EthereumAccount},
-  bytecode::Bytecode,
+  address::{
+    fil_address_to_h160,
+    h160_to_fil_address,
+    h160_to_id,
+    id_to_h160,
+    DelegatedAddress,
+    ID_ADDRESS_PREFIX,
+  },
+  bytecode::{
+    deposit_code,
+    validate_initcode,
+    Bytecode,
+    MAX_CODE_SIZE,
+    MAX_INITCODE_SIZE,
+  },
   execution::{execute, ExecutionState},
-  message::{CallKind, Message, EvmContractRuntimeConstructor},
-  output::{Output, StatusCode},
-  system::System,
+  host::{AccessStatus, Call, Host, Log, StorageStatus, TransactionContext},
+  message::{
+    ActorCallParams,
+    BridgeConstructorParams,
+    CallKind,
+    CallParams,
+    EvmContractRuntimeConstructor,
+    InvocationParams,
+    Message,
+  },
+  output::{
+    panic_description,
+    CallReturn,
+    CreateReturn,
+    InvocationReturn,
+    Output,
+    RevertReason,
+    StatusCode,
+    ERROR_SELECTOR,
+    PANIC_SELECTOR,
+  },
+  revision::Revision,
+  system::{
+    epoch_to_timestamp,
+    System,
+    BLOCKHASH_WINDOW,
+    BLOCK_GAS_LIMIT,
+    EPOCH_DURATION_SECONDS,
+    GENESIS_TIMESTAMP,
+  },
   transaction::{
+    intrinsic_gas,
+    AccessListItem,
     SignedTransaction,
     Transaction,
     TransactionAction,
     TransactionRecoveryId,
     TransactionSignature,
   },
-  uints::{H160, H256, U256, U512},
+  uints::{token_to_u256, u256_to_token, H160, H256, U256, U512},
 };
 
 #[macro_export]
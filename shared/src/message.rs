@@ -1,12 +1,22 @@
 use {
-  crate::{transaction::TransactionAction, SignedTransaction, H160, U256},
+  crate::{
+    host::TransactionContext,
+    revision::Revision,
+    transaction::{AccessListItem, TransactionAction},
+    SignedTransaction,
+    H160,
+    U256,
+  },
   bytes::Bytes,
   fil_actors_runtime::ActorError,
+  fvm_ipld_encoding::Cbor,
+  serde::{Deserialize, Serialize},
+  serde_tuple::{Deserialize_tuple, Serialize_tuple},
   std::fmt::Debug,
 };
 
 /// The kind of call-like instruction.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CallKind {
   Call,
   DelegateCall,
@@ -17,7 +27,7 @@ pub enum CallKind {
 
 /// The message describing an EVM call,
 /// including a zero-depth call from transaction origin.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
 pub struct Message {
   /// The kind of the call. For zero-depth calls `CallKind::Call` SHOULD be
   /// used.
@@ -49,6 +59,20 @@ impl TryFrom<SignedTransaction> for Message {
   type Error = ActorError;
 
   fn try_from(tx: SignedTransaction) -> Result<Self, Self::Error> {
+    // intrinsic gas is charged before any code runs, and only what
+    // remains of the gas limit is available to the execution.
+    let intrinsic_gas = tx.transaction.intrinsic_gas();
+    let gas = tx
+      .transaction
+      .gas_limit()
+      .checked_sub(intrinsic_gas)
+      .ok_or_else(|| {
+        ActorError::illegal_argument(format!(
+          "intrinsic gas too low: gas limit {}, intrinsic gas {intrinsic_gas}",
+          tx.transaction.gas_limit()
+        ))
+      })?;
+
     Ok(Message {
       kind: match tx.transaction.action() {
         TransactionAction::Call(_) => CallKind::Call,
@@ -56,7 +80,7 @@ impl TryFrom<SignedTransaction> for Message {
       },
       is_static: false,
       depth: 0,
-      gas: tx.transaction.gas_limit() as i64,
+      gas: gas.try_into().unwrap_or(i64::MAX),
       recipient: match tx.transaction.action() {
         TransactionAction::Call(addr) => addr,
         TransactionAction::Create => H160::zero(),
@@ -68,6 +92,50 @@ impl TryFrom<SignedTransaction> for Message {
   }
 }
 
+/// A message to be executed by an EVM contract actor along with the
+/// context of the transaction it belongs to.
+///
+/// Instances of this type are sent by the bridge to EVM runtime actors
+/// whenever contract code needs to run against the contract state.
+#[derive(Clone, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct InvocationParams {
+  pub message: Message,
+  pub context: TransactionContext,
+  /// Accounts and storage keys that are warm from the start (EIP-2930).
+  pub access_list: Vec<AccessListItem>,
+}
+
+impl Cbor for InvocationParams {}
+
+/// Parameters of `InvokeContract` when the EVM runtime actor is called
+/// directly by a native FVM actor or account rather than by the bridge.
+///
+/// The caller and the attached FIL become `CALLER` and `CALLVALUE`
+/// of the execution.
+#[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct ActorCallParams {
+  pub input_data: Bytes,
+  pub gas: u64,
+}
+
+impl Cbor for ActorCallParams {}
+
+/// An unsigned call that is executed against the current state without
+/// persisting any of its effects. This is what backs `eth_call`.
+///
+/// If `to` is `None` then `data` is treated as contract init code and
+/// the creation of a new contract is simulated.
+#[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct CallParams {
+  pub from: H160,
+  pub to: Option<H160>,
+  pub data: Bytes,
+  pub value: U256,
+  pub gas: u64,
+}
+
+impl Cbor for CallParams {}
+
 /// This type is used to construct a new instance of an EVM contract.
 /// Instances of this type are created by the bridge actor after a successful
 /// invocation of EVM contract constructor.
@@ -77,6 +145,9 @@ pub struct EvmContractRuntimeConstructor {
   pub bytecode: bytes::Bytes,
   pub registry: fvm_shared::address::Address,
   pub address: H160,
+  /// The revision of the bridge that created the contract, used for
+  /// messages that do not come through the bridge.
+  pub revision: Revision,
 }
 
 impl Debug for EvmContractRuntimeConstructor {
@@ -86,6 +157,20 @@ impl Debug for EvmContractRuntimeConstructor {
       .field("bytecode", &hex::encode(&self.bytecode))
       .field("registry", &self.registry)
       .field("address", &self.address)
+      .field("revision", &self.revision)
       .finish()
   }
 }
+
+/// Parameters of the bridge actor constructor.
+#[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct BridgeConstructorParams {
+  /// Code CID of the EVM runtime actor, instantiated for every contract.
+  pub runtime_cid: cid::Cid,
+  /// The revision all contracts created by this bridge are executed under.
+  pub revision: Revision,
+  /// The EIP-155 chain id signed transactions must be bound to.
+  pub chain_id: u64,
+}
+
+impl Cbor for BridgeConstructorParams {}
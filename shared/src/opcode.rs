@@ -1,10 +1,9 @@
-//! EVM Opcodes as of Berlin Hard Fork
+//! EVM Opcodes from the Berlin Hard Fork onwards
 //!
-//! On filecoin we will never have to replay blocks that are older
-//! than the release date of the FVM-EVM runtime, so supporting
-//! historic behavior is not needed.
+//! Which opcodes are defined depends on the [`Revision`] the code runs
+//! under, see [`OpCode::lookup`].
 
-use crate::output::StatusCode;
+use crate::{output::StatusCode, revision::Revision};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct OpCode {
@@ -12,7 +11,9 @@ pub struct OpCode {
   pub code: u8,
 
   /// cost of executing the opcode, subtracted from the
-  /// total gas limit when running bytecode.
+  /// total gas limit when running bytecode. The constants below
+  /// hold the price before any repricing, opcodes returned by
+  /// [`OpCode::lookup`] carry the price of the requested revision.
   pub price: u16,
 
   /// The number of stack items the instruction accesses during execution.
@@ -73,7 +74,7 @@ impl OpCode {
   };
   pub const BALANCE: OpCode = OpCode {
     code: 0x31,
-    price: WARM_STORAGE_READ_COST,
+    price: 700,
     stack_height_required: 1,
     stack_height_change: 0,
     name: "BALANCE",
@@ -101,14 +102,14 @@ impl OpCode {
   };
   pub const CALL: OpCode = OpCode {
     code: 0xf1,
-    price: WARM_STORAGE_READ_COST,
+    price: 700,
     stack_height_required: 7,
     stack_height_change: -6,
     name: "CALL",
   };
   pub const CALLCODE: OpCode = OpCode {
     code: 0xf2,
-    price: WARM_STORAGE_READ_COST,
+    price: 700,
     stack_height_required: 7,
     stack_height_change: -6,
     name: "CALLCODE",
@@ -192,7 +193,7 @@ impl OpCode {
   };
   pub const DELEGATECALL: OpCode = OpCode {
     code: 0xf4,
-    price: WARM_STORAGE_READ_COST,
+    price: 700,
     stack_height_required: 6,
     stack_height_change: -5,
     name: "DELEGATECALL",
@@ -339,21 +340,21 @@ impl OpCode {
   };
   pub const EXTCODECOPY: OpCode = OpCode {
     code: 0x3c,
-    price: WARM_STORAGE_READ_COST,
+    price: 700,
     stack_height_required: 4,
     stack_height_change: -4,
     name: "EXTCODECOPY",
   };
   pub const EXTCODEHASH: OpCode = OpCode {
     code: 0x3f,
-    price: WARM_STORAGE_READ_COST,
+    price: 700,
     stack_height_required: 1,
     stack_height_change: 0,
     name: "EXTCODEHASH",
   };
   pub const EXTCODESIZE: OpCode = OpCode {
     code: 0x3b,
-    price: WARM_STORAGE_READ_COST,
+    price: 700,
     stack_height_required: 1,
     stack_height_change: 0,
     name: "EXTCODESIZE",
@@ -470,6 +471,13 @@ impl OpCode {
     stack_height_change: -1,
     name: "LT",
   };
+  pub const MCOPY: OpCode = OpCode {
+    code: 0x5e,
+    price: 3,
+    stack_height_required: 3,
+    stack_height_change: -3,
+    name: "MCOPY",
+  };
   pub const MLOAD: OpCode = OpCode {
     code: 0x51,
     price: 3,
@@ -561,6 +569,13 @@ impl OpCode {
     stack_height_change: -1,
     name: "POP",
   };
+  pub const PUSH0: OpCode = OpCode {
+    code: 0x5f,
+    price: 2,
+    stack_height_required: 0,
+    stack_height_change: 1,
+    name: "PUSH0",
+  };
   pub const PUSH1: OpCode = OpCode {
     code: 0x60,
     price: 3,
@@ -871,7 +886,7 @@ impl OpCode {
   };
   pub const SLOAD: OpCode = OpCode {
     code: 0x54,
-    price: WARM_STORAGE_READ_COST,
+    price: 800,
     stack_height_required: 1,
     stack_height_change: 0,
     name: "SLOAD",
@@ -899,7 +914,7 @@ impl OpCode {
   };
   pub const STATICCALL: OpCode = OpCode {
     code: 0xfa,
-    price: WARM_STORAGE_READ_COST,
+    price: 700,
     stack_height_required: 6,
     stack_height_change: -5,
     name: "STATICCALL",
@@ -1037,6 +1052,20 @@ impl OpCode {
     stack_height_change: 1,
     name: "TIMESTAMP",
   };
+  pub const TLOAD: OpCode = OpCode {
+    code: 0x5c,
+    price: WARM_STORAGE_READ_COST,
+    stack_height_required: 1,
+    stack_height_change: 0,
+    name: "TLOAD",
+  };
+  pub const TSTORE: OpCode = OpCode {
+    code: 0x5d,
+    price: WARM_STORAGE_READ_COST,
+    stack_height_required: 2,
+    stack_height_change: -2,
+    name: "TSTORE",
+  };
   pub const XOR: OpCode = OpCode {
     code: 0x18,
     price: 3,
@@ -1052,163 +1081,239 @@ impl std::fmt::Display for OpCode {
   }
 }
 
-impl TryFrom<u8> for OpCode {
-  type Error = StatusCode;
+impl OpCode {
+  /// Returns the opcode encoded by `code` in `revision`.
+  ///
+  /// The returned opcode carries its price in `revision`. Fails with
+  /// `UndefinedInstruction` if `code` is not assigned, or was assigned in
+  /// a later revision.
+  pub fn lookup(code: u8, revision: Revision) -> Result<OpCode, StatusCode> {
+    OPCODE_TABLES[revision as usize][code as usize]
+      .ok_or(StatusCode::UndefinedInstruction)
+  }
+}
+
+/// Opcodes defined in Berlin.
+const OPCODES: [OpCode; 142] = [
+  OpCode::STOP,
+  OpCode::ADD,
+  OpCode::MUL,
+  OpCode::SUB,
+  OpCode::DIV,
+  OpCode::SDIV,
+  OpCode::MOD,
+  OpCode::SMOD,
+  OpCode::ADDMOD,
+  OpCode::MULMOD,
+  OpCode::EXP,
+  OpCode::SIGNEXTEND,
+  OpCode::LT,
+  OpCode::GT,
+  OpCode::SLT,
+  OpCode::SGT,
+  OpCode::EQ,
+  OpCode::ISZERO,
+  OpCode::AND,
+  OpCode::OR,
+  OpCode::XOR,
+  OpCode::NOT,
+  OpCode::BYTE,
+  OpCode::SHL,
+  OpCode::SHR,
+  OpCode::SAR,
+  OpCode::KECCAK256,
+  OpCode::ADDRESS,
+  OpCode::BALANCE,
+  OpCode::ORIGIN,
+  OpCode::CALLER,
+  OpCode::CALLVALUE,
+  OpCode::CALLDATALOAD,
+  OpCode::CALLDATASIZE,
+  OpCode::CALLDATACOPY,
+  OpCode::CODESIZE,
+  OpCode::CODECOPY,
+  OpCode::GASPRICE,
+  OpCode::EXTCODESIZE,
+  OpCode::EXTCODECOPY,
+  OpCode::RETURNDATASIZE,
+  OpCode::RETURNDATACOPY,
+  OpCode::EXTCODEHASH,
+  OpCode::BLOCKHASH,
+  OpCode::COINBASE,
+  OpCode::TIMESTAMP,
+  OpCode::NUMBER,
+  OpCode::DIFFICULTY,
+  OpCode::GASLIMIT,
+  OpCode::CHAINID,
+  OpCode::SELFBALANCE,
+  OpCode::POP,
+  OpCode::MLOAD,
+  OpCode::MSTORE,
+  OpCode::MSTORE8,
+  OpCode::SLOAD,
+  OpCode::SSTORE,
+  OpCode::JUMP,
+  OpCode::JUMPI,
+  OpCode::PC,
+  OpCode::MSIZE,
+  OpCode::GAS,
+  OpCode::JUMPDEST,
+  OpCode::PUSH1,
+  OpCode::PUSH2,
+  OpCode::PUSH3,
+  OpCode::PUSH4,
+  OpCode::PUSH5,
+  OpCode::PUSH6,
+  OpCode::PUSH7,
+  OpCode::PUSH8,
+  OpCode::PUSH9,
+  OpCode::PUSH10,
+  OpCode::PUSH11,
+  OpCode::PUSH12,
+  OpCode::PUSH13,
+  OpCode::PUSH14,
+  OpCode::PUSH15,
+  OpCode::PUSH16,
+  OpCode::PUSH17,
+  OpCode::PUSH18,
+  OpCode::PUSH19,
+  OpCode::PUSH20,
+  OpCode::PUSH21,
+  OpCode::PUSH22,
+  OpCode::PUSH23,
+  OpCode::PUSH24,
+  OpCode::PUSH25,
+  OpCode::PUSH26,
+  OpCode::PUSH27,
+  OpCode::PUSH28,
+  OpCode::PUSH29,
+  OpCode::PUSH30,
+  OpCode::PUSH31,
+  OpCode::PUSH32,
+  OpCode::DUP1,
+  OpCode::DUP2,
+  OpCode::DUP3,
+  OpCode::DUP4,
+  OpCode::DUP5,
+  OpCode::DUP6,
+  OpCode::DUP7,
+  OpCode::DUP8,
+  OpCode::DUP9,
+  OpCode::DUP10,
+  OpCode::DUP11,
+  OpCode::DUP12,
+  OpCode::DUP13,
+  OpCode::DUP14,
+  OpCode::DUP15,
+  OpCode::DUP16,
+  OpCode::SWAP1,
+  OpCode::SWAP2,
+  OpCode::SWAP3,
+  OpCode::SWAP4,
+  OpCode::SWAP5,
+  OpCode::SWAP6,
+  OpCode::SWAP7,
+  OpCode::SWAP8,
+  OpCode::SWAP9,
+  OpCode::SWAP10,
+  OpCode::SWAP11,
+  OpCode::SWAP12,
+  OpCode::SWAP13,
+  OpCode::SWAP14,
+  OpCode::SWAP15,
+  OpCode::SWAP16,
+  OpCode::LOG0,
+  OpCode::LOG1,
+  OpCode::LOG2,
+  OpCode::LOG3,
+  OpCode::LOG4,
+  OpCode::CREATE,
+  OpCode::CALL,
+  OpCode::CALLCODE,
+  OpCode::RETURN,
+  OpCode::DELEGATECALL,
+  OpCode::CREATE2,
+  OpCode::STATICCALL,
+  OpCode::REVERT,
+  OpCode::INVALID,
+  OpCode::SELFDESTRUCT,
+];
+
+/// Opcodes introduced after Berlin, with the revision that introduced them.
+const INTRODUCED: [(OpCode, Revision); 5] = [
+  (OpCode::BASEFEE, Revision::London),
+  (OpCode::PUSH0, Revision::Shanghai),
+  (OpCode::TLOAD, Revision::Cancun),
+  (OpCode::TSTORE, Revision::Cancun),
+  (OpCode::MCOPY, Revision::Cancun),
+];
+
+/// Opcodes whose price was changed by a hard fork, with the revision that
+/// changed it and the new price.
+const REPRICED: [(OpCode, Revision, u16); 9] = [
+  // EIP-2929: the warm access cost is charged upfront, the cold access
+  // surcharge when the instruction executes.
+  (OpCode::SLOAD, Revision::Berlin, WARM_STORAGE_READ_COST),
+  (OpCode::BALANCE, Revision::Berlin, WARM_STORAGE_READ_COST),
+  (
+    OpCode::EXTCODESIZE,
+    Revision::Berlin,
+    WARM_STORAGE_READ_COST,
+  ),
+  (
+    OpCode::EXTCODECOPY,
+    Revision::Berlin,
+    WARM_STORAGE_READ_COST,
+  ),
+  (
+    OpCode::EXTCODEHASH,
+    Revision::Berlin,
+    WARM_STORAGE_READ_COST,
+  ),
+  (OpCode::CALL, Revision::Berlin, WARM_STORAGE_READ_COST),
+  (OpCode::CALLCODE, Revision::Berlin, WARM_STORAGE_READ_COST),
+  (
+    OpCode::DELEGATECALL,
+    Revision::Berlin,
+    WARM_STORAGE_READ_COST,
+  ),
+  (OpCode::STATICCALL, Revision::Berlin, WARM_STORAGE_READ_COST),
+];
 
-  fn try_from(value: u8) -> Result<Self, Self::Error> {
-    // todo: optimize and turn it into a jump table
-    const OPCODES: [OpCode; 143] = [
-      OpCode::STOP,
-      OpCode::ADD,
-      OpCode::MUL,
-      OpCode::SUB,
-      OpCode::DIV,
-      OpCode::SDIV,
-      OpCode::MOD,
-      OpCode::SMOD,
-      OpCode::ADDMOD,
-      OpCode::MULMOD,
-      OpCode::EXP,
-      OpCode::SIGNEXTEND,
-      OpCode::LT,
-      OpCode::GT,
-      OpCode::SLT,
-      OpCode::SGT,
-      OpCode::EQ,
-      OpCode::ISZERO,
-      OpCode::AND,
-      OpCode::OR,
-      OpCode::XOR,
-      OpCode::NOT,
-      OpCode::BYTE,
-      OpCode::SHL,
-      OpCode::SHR,
-      OpCode::SAR,
-      OpCode::KECCAK256,
-      OpCode::ADDRESS,
-      OpCode::BALANCE,
-      OpCode::ORIGIN,
-      OpCode::CALLER,
-      OpCode::CALLVALUE,
-      OpCode::CALLDATALOAD,
-      OpCode::CALLDATASIZE,
-      OpCode::CALLDATACOPY,
-      OpCode::CODESIZE,
-      OpCode::CODECOPY,
-      OpCode::GASPRICE,
-      OpCode::EXTCODESIZE,
-      OpCode::EXTCODECOPY,
-      OpCode::RETURNDATASIZE,
-      OpCode::RETURNDATACOPY,
-      OpCode::EXTCODEHASH,
-      OpCode::BLOCKHASH,
-      OpCode::COINBASE,
-      OpCode::TIMESTAMP,
-      OpCode::NUMBER,
-      OpCode::DIFFICULTY,
-      OpCode::GASLIMIT,
-      OpCode::CHAINID,
-      OpCode::SELFBALANCE,
-      OpCode::BASEFEE,
-      OpCode::POP,
-      OpCode::MLOAD,
-      OpCode::MSTORE,
-      OpCode::MSTORE8,
-      OpCode::SLOAD,
-      OpCode::SSTORE,
-      OpCode::JUMP,
-      OpCode::JUMPI,
-      OpCode::PC,
-      OpCode::MSIZE,
-      OpCode::GAS,
-      OpCode::JUMPDEST,
-      OpCode::PUSH1,
-      OpCode::PUSH2,
-      OpCode::PUSH3,
-      OpCode::PUSH4,
-      OpCode::PUSH5,
-      OpCode::PUSH6,
-      OpCode::PUSH7,
-      OpCode::PUSH8,
-      OpCode::PUSH9,
-      OpCode::PUSH10,
-      OpCode::PUSH11,
-      OpCode::PUSH12,
-      OpCode::PUSH13,
-      OpCode::PUSH14,
-      OpCode::PUSH15,
-      OpCode::PUSH16,
-      OpCode::PUSH17,
-      OpCode::PUSH18,
-      OpCode::PUSH19,
-      OpCode::PUSH20,
-      OpCode::PUSH21,
-      OpCode::PUSH22,
-      OpCode::PUSH23,
-      OpCode::PUSH24,
-      OpCode::PUSH25,
-      OpCode::PUSH26,
-      OpCode::PUSH27,
-      OpCode::PUSH28,
-      OpCode::PUSH29,
-      OpCode::PUSH30,
-      OpCode::PUSH31,
-      OpCode::PUSH32,
-      OpCode::DUP1,
-      OpCode::DUP2,
-      OpCode::DUP3,
-      OpCode::DUP4,
-      OpCode::DUP5,
-      OpCode::DUP6,
-      OpCode::DUP7,
-      OpCode::DUP8,
-      OpCode::DUP9,
-      OpCode::DUP10,
-      OpCode::DUP11,
-      OpCode::DUP12,
-      OpCode::DUP13,
-      OpCode::DUP14,
-      OpCode::DUP15,
-      OpCode::DUP16,
-      OpCode::SWAP1,
-      OpCode::SWAP2,
-      OpCode::SWAP3,
-      OpCode::SWAP4,
-      OpCode::SWAP5,
-      OpCode::SWAP6,
-      OpCode::SWAP7,
-      OpCode::SWAP8,
-      OpCode::SWAP9,
-      OpCode::SWAP10,
-      OpCode::SWAP11,
-      OpCode::SWAP12,
-      OpCode::SWAP13,
-      OpCode::SWAP14,
-      OpCode::SWAP15,
-      OpCode::SWAP16,
-      OpCode::LOG0,
-      OpCode::LOG1,
-      OpCode::LOG2,
-      OpCode::LOG3,
-      OpCode::LOG4,
-      OpCode::CREATE,
-      OpCode::CALL,
-      OpCode::CALLCODE,
-      OpCode::RETURN,
-      OpCode::DELEGATECALL,
-      OpCode::CREATE2,
-      OpCode::STATICCALL,
-      OpCode::REVERT,
-      OpCode::INVALID,
-      OpCode::SELFDESTRUCT,
-    ];
+/// The opcodes of every revision, indexed by their byte.
+static OPCODE_TABLES: [[Option<OpCode>; 256]; 4] = [
+  opcode_table(Revision::Berlin),
+  opcode_table(Revision::London),
+  opcode_table(Revision::Shanghai),
+  opcode_table(Revision::Cancun),
+];
 
-    for op in OPCODES {
-      if op == value {
-        return Ok(op);
-      }
+const fn opcode_table(revision: Revision) -> [Option<OpCode>; 256] {
+  let mut table = [None; 256];
+
+  let mut i = 0;
+  while i < OPCODES.len() {
+    table[OPCODES[i].code as usize] = Some(OPCODES[i]);
+    i += 1;
+  }
+
+  let mut i = 0;
+  while i < INTRODUCED.len() {
+    let (op, since) = INTRODUCED[i];
+    if revision as u8 >= since as u8 {
+      table[op.code as usize] = Some(op);
     }
+    i += 1;
+  }
 
-    Err(StatusCode::UndefinedInstruction)
+  let mut i = 0;
+  while i < REPRICED.len() {
+    let (op, since, price) = REPRICED[i];
+    if revision as u8 >= since as u8 {
+      table[op.code as usize] = Some(OpCode { price, ..op });
+    }
+    i += 1;
   }
+
+  table
 }
@@ -1,7 +1,16 @@
 use {
+  crate::{
+    abi::{self, ParamType},
+    host::Log,
+    message::Message,
+    revision::Revision,
+    H160,
+    U256,
+  },
   bytes::Bytes,
   fvm_ipld_encoding::Cbor,
   serde::{Deserialize, Serialize},
+  serde_tuple::{Deserialize_tuple, Serialize_tuple},
   strum_macros::Display,
 };
 
@@ -12,12 +21,274 @@ pub struct Output {
   pub status_code: StatusCode,
   /// How much gas was left after execution
   pub gas_left: i64,
+  /// Refund counter accumulated by the execution, zero if reverted. The
+  /// share actually paid to the sender is capped against the gas used by
+  /// the whole transaction, see [`Revision::capped_refund`].
+  pub gas_refund: i64,
   /// Output data returned.
   pub output_data: Bytes,
   // indicates if revert was requested
   pub reverted: bool,
 }
 
+impl Output {
+  /// The decoded revert reason if execution was reverted with data.
+  pub fn revert_reason(&self) -> Option<RevertReason> {
+    match self.reverted {
+      true => RevertReason::decode(&self.output_data),
+      false => None,
+    }
+  }
+
+  /// Human readable description of why execution did not succeed.
+  pub fn error_message(&self) -> String {
+    match self.reverted {
+      true => revert_message(&self.output_data),
+      false => self.status_code.to_string(),
+    }
+  }
+}
+
+/// Result of a call, returned to callers outside of the EVM.
+#[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct CallReturn {
+  /// Final status of the execution.
+  pub status_code: StatusCode,
+  /// Amount of gas consumed by the execution.
+  pub gas_used: u64,
+  /// Amount of the gas used that is refunded to the sender.
+  pub gas_refund: u64,
+  /// Data returned by a successful execution.
+  pub output_data: Bytes,
+  /// Data passed to REVERT, empty unless execution was reverted.
+  /// See [`CallReturn::revert_reason`] for its decoded form.
+  pub revert_data: Bytes,
+}
+
+impl Cbor for CallReturn {}
+
+impl CallReturn {
+  /// Summarizes the outcome of executing `message`.
+  ///
+  /// Failures other than REVERT consume all gas made available to
+  /// the message and produce no output.
+  pub fn new(message: &Message, result: Result<Output, StatusCode>) -> Self {
+    match result {
+      Ok(output) => {
+        let gas_used = (message.gas - output.gas_left.max(0)).max(0) as u64;
+        if output.reverted {
+          Self {
+            status_code: StatusCode::Revert,
+            gas_used,
+            gas_refund: 0,
+            output_data: Bytes::new(),
+            revert_data: output.output_data,
+          }
+        } else {
+          Self {
+            status_code: output.status_code,
+            gas_used,
+            gas_refund: output.gas_refund.max(0) as u64,
+            output_data: output.output_data,
+            revert_data: Bytes::new(),
+          }
+        }
+      }
+      Err(status_code) => Self {
+        status_code,
+        gas_used: message.gas.max(0) as u64,
+        gas_refund: 0,
+        output_data: Bytes::new(),
+        revert_data: Bytes::new(),
+      },
+    }
+  }
+
+  /// Adds the intrinsic gas of the transaction that executed this call to
+  /// the gas used, and caps the refund against that total (EIP-3529).
+  pub fn charge_intrinsic_gas(&mut self, intrinsic_gas: u64, revision: Revision) {
+    self.gas_used += intrinsic_gas;
+    self.gas_refund = revision.capped_refund(self.gas_refund as i64, self.gas_used);
+  }
+
+  /// True if execution ran to completion without reverting.
+  pub fn is_success(&self) -> bool {
+    self.status_code == StatusCode::Success
+  }
+
+  /// The decoded revert reason if execution was reverted with data.
+  pub fn revert_reason(&self) -> Option<RevertReason> {
+    RevertReason::decode(&self.revert_data)
+  }
+
+  /// Human readable description of why execution did not succeed.
+  pub fn error_message(&self) -> String {
+    match self.status_code {
+      StatusCode::Revert => revert_message(&self.revert_data),
+      ref status => status.to_string(),
+    }
+  }
+}
+
+/// Selector of `Error(string)`, used by `require` and `revert` with a message.
+pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Selector of `Panic(uint256)`, used by failing `assert` and checked
+/// arithmetic.
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Reason passed to REVERT, decoded according to Solidity conventions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RevertReason {
+  /// `Error(string)` raised by `require(cond, "message")` or
+  /// `revert("message")`.
+  Error(String),
+
+  /// `Panic(uint256)` raised by the compiler generated checks.
+  Panic(U256),
+
+  /// A custom error, identified by the selector of its signature.
+  Custom { selector: [u8; 4], data: Bytes },
+
+  /// Data that does not follow any known convention.
+  Raw(Bytes),
+}
+
+impl RevertReason {
+  /// Decodes the data passed to REVERT, `None` if there is no data.
+  pub fn decode(data: &[u8]) -> Option<Self> {
+    if data.is_empty() {
+      return None;
+    }
+    if data.len() < 4 {
+      return Some(RevertReason::Raw(Bytes::copy_from_slice(data)));
+    }
+
+    let (selector, args) = data.split_at(4);
+    let decoded = match selector {
+      s if s == ERROR_SELECTOR => abi::decode(&[ParamType::String], args)
+        .ok()
+        .and_then(|mut tokens| tokens.pop()?.into_string())
+        .map(RevertReason::Error),
+      s if s == PANIC_SELECTOR => abi::decode(&[ParamType::Uint(256)], args)
+        .ok()
+        .and_then(|mut tokens| tokens.pop()?.into_uint())
+        .map(RevertReason::Panic),
+      _ => Some(RevertReason::Custom {
+        selector: selector.try_into().unwrap(),
+        data: Bytes::copy_from_slice(args),
+      }),
+    };
+
+    // a well known selector with malformed arguments
+    Some(decoded.unwrap_or_else(|| RevertReason::Raw(Bytes::copy_from_slice(data))))
+  }
+}
+
+/// Meaning of a Solidity panic code.
+pub fn panic_description(code: U256) -> &'static str {
+  if code > U256::from(u8::MAX) {
+    return "unknown panic";
+  }
+  match code.low_u32() {
+    0x00 => "generic compiler panic",
+    0x01 => "assertion failed",
+    0x11 => "arithmetic overflow or underflow",
+    0x12 => "division or modulo by zero",
+    0x21 => "invalid enum value",
+    0x22 => "invalid storage byte array encoding",
+    0x31 => "pop on empty array",
+    0x32 => "array index out of bounds",
+    0x41 => "out of memory",
+    0x51 => "call to uninitialized internal function",
+    _ => "unknown panic",
+  }
+}
+
+impl std::fmt::Display for RevertReason {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      RevertReason::Error(message) => write!(f, "{message}"),
+      RevertReason::Panic(code) if code.bits() <= 64 => write!(
+        f,
+        "panic: {} (0x{:02x})",
+        panic_description(*code),
+        code.low_u64()
+      ),
+      RevertReason::Panic(code) => {
+        write!(f, "panic: {} ({code})", panic_description(*code))
+      }
+      RevertReason::Custom { selector, .. } => {
+        write!(f, "custom error 0x{}", hex::encode(selector))
+      }
+      RevertReason::Raw(_) => write!(f, "unknown reason"),
+    }
+  }
+}
+
+fn revert_message(data: &[u8]) -> String {
+  match RevertReason::decode(data) {
+    Some(reason) => {
+      format!(
+        "execution reverted: {reason} (data: 0x{})",
+        hex::encode(data)
+      )
+    }
+    None => "execution reverted".into(),
+  }
+}
+
+/// Result of a contract creation transaction.
+///
+/// A failed creation still consumes the nonce of the sender, so it is
+/// returned with its status rather than as an error.
+#[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct CreateReturn {
+  /// Final status of the constructor, the contract is only deployed
+  /// on success.
+  pub status_code: StatusCode,
+  /// Address of the deployed contract.
+  pub address: H160,
+  /// Amount of gas consumed by the transaction, including intrinsic gas
+  /// and the code deposit.
+  pub gas_used: u64,
+  /// Amount of the gas used that is refunded to the sender.
+  pub gas_refund: u64,
+  /// Data passed to REVERT, empty unless the constructor was reverted.
+  pub revert_data: Bytes,
+  /// Logs emitted by the constructor, empty unless it succeeded.
+  pub logs: Vec<Log>,
+}
+
+impl CreateReturn {
+  /// True if the contract was deployed.
+  pub fn is_success(&self) -> bool {
+    self.status_code == StatusCode::Success
+  }
+}
+
+impl Cbor for CreateReturn {}
+
+/// Result of a message executed by an EVM contract actor
+/// on behalf of the bridge.
+#[derive(Clone, Debug, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct InvocationReturn {
+  pub result: CallReturn,
+
+  /// Human readable form of [`CallReturn::revert_reason`], set if the
+  /// execution was reverted with data.
+  pub revert_reason: Option<String>,
+
+  /// Set if the contract executed SELFDESTRUCT, the account that
+  /// receives the contract balance.
+  pub selfdestruct: Option<H160>,
+
+  /// Logs emitted by the contract, empty unless execution succeeded.
+  pub logs: Vec<Log>,
+}
+
+impl Cbor for InvocationReturn {}
+
 /// Message status code.
 #[must_use]
 #[derive(Clone, Debug, Display, PartialEq, Serialize, Deserialize)]
@@ -103,3 +374,69 @@ pub enum StatusCode {
 }
 
 impl Cbor for StatusCode {}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::abi::{encode, Token},
+  };
+
+  fn revert_data(selector: [u8; 4], args: &[Token]) -> Vec<u8> {
+    selector.into_iter().chain(encode(args)).collect()
+  }
+
+  #[test]
+  fn decode_error_string() {
+    let data = revert_data(ERROR_SELECTOR, &["Ownable: caller is not the owner".into()]);
+    assert_eq!(
+      RevertReason::decode(&data),
+      Some(RevertReason::Error(
+        "Ownable: caller is not the owner".into()
+      ))
+    );
+    assert!(revert_message(&data).starts_with(
+      "execution reverted: Ownable: caller is not the owner (data: 0x08c379a0"
+    ));
+  }
+
+  #[test]
+  fn decode_panic_codes() {
+    let data = revert_data(PANIC_SELECTOR, &[Token::Uint(0x11.into())]);
+    let reason = RevertReason::decode(&data).unwrap();
+    assert_eq!(reason, RevertReason::Panic(0x11.into()));
+    assert_eq!(
+      reason.to_string(),
+      "panic: arithmetic overflow or underflow (0x11)"
+    );
+    assert_eq!(panic_description(0x12.into()), "division or modulo by zero");
+    assert_eq!(panic_description(0x32.into()), "array index out of bounds");
+    assert_eq!(panic_description(U256::MAX), "unknown panic");
+  }
+
+  #[test]
+  fn decode_custom_and_raw() {
+    // error InsufficientBalance(uint256 available, uint256 required)
+    let selector = abi::selector("InsufficientBalance(uint256,uint256)");
+    let data = revert_data(selector, &[Token::Uint(1.into()), Token::Uint(2.into())]);
+    assert_eq!(
+      RevertReason::decode(&data),
+      Some(RevertReason::Custom {
+        selector,
+        data: Bytes::copy_from_slice(&data[4..]),
+      })
+    );
+
+    assert_eq!(RevertReason::decode(&[]), None);
+    assert_eq!(
+      RevertReason::decode(&[1, 2]),
+      Some(RevertReason::Raw(Bytes::from_static(&[1, 2])))
+    );
+
+    // Error(string) selector followed by garbage
+    assert_eq!(
+      RevertReason::decode(&ERROR_SELECTOR),
+      Some(RevertReason::Raw(Bytes::copy_from_slice(&ERROR_SELECTOR)))
+    );
+  }
+}
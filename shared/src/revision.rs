@@ -0,0 +1,63 @@
+//! Ethereum hard forks supported by the interpreter.
+//!
+//! On filecoin we will never have to replay blocks that are older
+//! than the release date of the FVM-EVM runtime, so revisions before
+//! Berlin are not supported.
+
+use serde::{Deserialize, Serialize};
+
+/// The hard fork whose rules are used to execute bytecode.
+///
+/// The revision selects which opcodes are defined and what they cost,
+/// as well as fork dependent behavior such as SELFDESTRUCT semantics.
+/// It is chosen when the bridge is deployed and passed on to every
+/// contract it creates. Revisions are ordered by activation, so
+/// `revision >= Revision::London` checks whether London rules apply.
+#[derive(
+  Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Revision {
+  /// Access lists and cold/warm state access costs (EIP-2929, EIP-2930).
+  Berlin,
+  /// BASEFEE (EIP-3198) and reduced refunds (EIP-3529).
+  London,
+  /// PUSH0 (EIP-3855).
+  Shanghai,
+  /// Transient storage (EIP-1153), MCOPY (EIP-5656) and SELFDESTRUCT
+  /// restricted to contracts created in the same transaction (EIP-6780).
+  Cancun,
+}
+
+impl Revision {
+  /// The most recent revision supported by the interpreter.
+  pub const LATEST: Revision = Revision::Cancun;
+
+  /// Whether SELFDESTRUCT only deletes contracts that were created in the
+  /// same transaction (EIP-6780). Otherwise it always deletes the contract.
+  pub fn selfdestruct_same_tx_only(self) -> bool {
+    self >= Revision::Cancun
+  }
+
+  /// Refund for clearing a storage item (EIP-2200), reduced in London
+  /// (EIP-3529).
+  pub fn sstore_clears_refund(self) -> i64 {
+    match self {
+      Revision::Berlin => 15000,
+      _ => 4800,
+    }
+  }
+
+  /// Refunds are capped to the gas used divided by this (EIP-3529).
+  pub fn max_refund_quotient(self) -> u64 {
+    match self {
+      Revision::Berlin => 2,
+      _ => 5,
+    }
+  }
+
+  /// The share of `gas_refund` paid back to a transaction that used
+  /// `gas_used` gas in total, intrinsic gas included.
+  pub fn capped_refund(self, gas_refund: i64, gas_used: u64) -> u64 {
+    (gas_refund.max(0) as u64).min(gas_used / self.max_refund_quotient())
+  }
+}
@@ -1,76 +1,126 @@
 use {
-  crate::{message::Message, output::StatusCode, Output, SignedTransaction, H160, U256},
+  crate::{
+    address::fil_address_to_h160,
+    host::{AccessStatus, Call, Host, Log, StorageStatus, TransactionContext},
+    output::StatusCode,
+    revision::Revision,
+    transaction::AccessListItem,
+    uints::token_to_u256,
+    EthereumAccount,
+    Output,
+    SignedTransaction,
+    H160,
+    U256,
+  },
   bytes::Bytes,
   cid::Cid,
-  fil_actors_runtime::{runtime::Runtime, ActorError},
+  fil_actors_runtime::{runtime::Runtime, ActorError, REWARD_ACTOR_ADDR},
   fvm_ipld_blockstore::Blockstore,
+  fvm_ipld_encoding::RawBytes,
   fvm_ipld_hamt::Hamt,
-  fvm_shared::address::Address,
-  std::{cell::RefCell, collections::HashSet},
+  fvm_shared::{address::Address, bigint::BigInt, clock::ChainEpoch},
+  sha3::{Digest, Keccak256},
+  std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+  },
 };
 
-/// Info sourced from the current transaction and block
-#[derive(Clone, Debug)]
-pub struct TransactionContext {
-  /// The transaction gas price.
-  pub tx_gas_price: U256,
-  /// The transaction origin account.
-  pub tx_origin: H160,
-  /// The miner of the block.
-  pub block_coinbase: H160,
-  /// The block number.
-  pub block_number: u64,
-  /// The block timestamp.
-  pub block_timestamp: u64,
-  /// The block gas limit.
-  pub block_gas_limit: u64,
-  /// The block difficulty.
-  pub block_difficulty: U256,
-  /// The blockchain's ChainID.
-  pub chain_id: U256,
-  /// The block base fee per gas (EIP-1559, EIP-3198).
-  pub block_base_fee: U256,
-}
+/// Bridge actor method that returns the `EthereumAccount` of an address.
+const BRIDGE_GET_ACCOUNT_METHOD_NUM: u64 = 5;
 
-/// State access status (EIP-2929).
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum AccessStatus {
-  Cold,
-  Warm,
-}
+/// Unix timestamp of the Filecoin mainnet genesis block.
+pub const GENESIS_TIMESTAMP: u64 = 1598306400;
 
-impl Default for AccessStatus {
-  fn default() -> Self {
-    Self::Cold
-  }
+/// Duration of a Filecoin epoch in seconds.
+pub const EPOCH_DURATION_SECONDS: u64 = 30;
+
+/// Gas limit reported by GASLIMIT. EVM gas is not FVM gas, so this is
+/// the Ethereum mainnet block gas limit rather than the Filecoin one.
+pub const BLOCK_GAS_LIMIT: u64 = 30_000_000;
+
+/// Domain separation tag of the beacon randomness exposed as PREVRANDAO.
+const PREVRANDAO_DOMAIN_SEPARATION_TAG: i64 = 10;
+
+/// Number of most recent epochs whose hash is available to BLOCKHASH.
+pub const BLOCKHASH_WINDOW: u64 = 256;
+
+/// True if BLOCKHASH executed at `current` can observe `requested`.
+fn in_blockhash_window(current: u64, requested: u64) -> bool {
+  requested < current && current - requested <= BLOCKHASH_WINDOW
 }
 
-#[derive(Clone, Copy, Debug)]
-pub enum StorageStatus {
-  /// The value of a storage item has been left unchanged: 0 -> 0 and X -> X.
-  Unchanged,
-  /// The value of a storage item has been modified: X -> Y.
-  Modified,
-  /// A storage item has been modified after being modified before: X -> Y -> Z.
-  ModifiedAgain,
-  /// A new storage item has been added: 0 -> X.
-  Added,
-  /// A storage item has been deleted: X -> 0.
-  Deleted,
+/// Converts a Filecoin epoch to the unix timestamp of its tipset.
+pub fn epoch_to_timestamp(epoch: ChainEpoch) -> u64 {
+  GENESIS_TIMESTAMP + epoch.max(0) as u64 * EPOCH_DURATION_SECONDS
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub enum Call<'a> {
-  Call(&'a Message),
-  Create(&'a Message),
+impl TransactionContext {
+  /// Creates a context for a message originating from `origin` that is
+  /// executed under the rules of `revision`.
+  ///
+  /// Block information is read from the FVM. Filecoin exposes no block
+  /// producer to actors, so COINBASE is the reward actor that pays block
+  /// producers. DIFFICULTY is PREVRANDAO (EIP-4399), drawn from the drand
+  /// beacon of the current epoch.
+  pub fn new(
+    origin: H160,
+    gas_price: U256,
+    chain_id: U256,
+    revision: Revision,
+  ) -> Result<Self, ActorError> {
+    let epoch = fvm_sdk::network::curr_epoch();
+    let randomness =
+      fvm_sdk::rand::get_beacon_randomness(PREVRANDAO_DOMAIN_SEPARATION_TAG, epoch, &[])
+        .map_err(|e| {
+          ActorError::illegal_state(format!("failed to draw randomness: {e:?}"))
+        })?;
+
+    Ok(Self {
+      tx_gas_price: gas_price,
+      tx_origin: origin,
+      block_coinbase: fil_address_to_h160(&REWARD_ACTOR_ADDR).unwrap_or_default(),
+      block_number: epoch.max(0) as u64,
+      block_timestamp: epoch_to_timestamp(epoch),
+      block_gas_limit: BLOCK_GAS_LIMIT,
+      block_difficulty: U256::from_big_endian(&randomness),
+      chain_id,
+      block_base_fee: token_to_u256(&fvm_sdk::network::base_fee()).unwrap_or_default(),
+      revision,
+    })
+  }
+
+  /// Creates the context of a signed transaction.
+  pub fn for_transaction(
+    tx: &SignedTransaction,
+    revision: Revision,
+  ) -> Result<Self, ActorError> {
+    Self::new(
+      tx.sender_address()?,
+      tx.gas_price(),
+      tx.chain_id().unwrap_or_default().into(),
+      revision,
+    )
+  }
 }
 
 /// Platform Abstraction Layer
 /// that bridges the FVM world to EVM world
 pub struct System<'r, BS: Blockstore> {
   state: RefCell<Hamt<&'r BS, U256, U256>>,
+  /// Transient storage (EIP-1153), never persisted. A system serves a
+  /// single transaction, so it is discarded when the transaction ends.
+  transient_storage: RefCell<HashMap<(H160, U256), U256>>,
+  /// Value of every written slot at the start of the transaction, used
+  /// to price SSTORE (EIP-2200).
+  original_storage: RefCell<HashMap<U256, U256>>,
   access_list: RefCell<HashSet<U256>>,
-  _bridge: Address,
+  accessed_accounts: RefCell<HashSet<H160>>,
+  selfdestruct_beneficiary: RefCell<Option<H160>>,
+  /// Logs emitted by the contract, returned to the bridge if the
+  /// execution succeeds.
+  logs: RefCell<Vec<Log>>,
+  bridge: Address,
   self_address: H160,
   context: TransactionContext,
 }
@@ -82,22 +132,57 @@ impl<'r, BS: Blockstore> System<'r, BS> {
     bridge: Address,
     self_address: H160,
     tx: &SignedTransaction,
+    revision: Revision,
+  ) -> anyhow::Result<Self> {
+    Self::with_context(
+      state_cid,
+      runtime,
+      bridge,
+      self_address,
+      TransactionContext::for_transaction(tx, revision)?,
+      tx.transaction.access_list(),
+    )
+  }
+
+  /// Creates a system rooted at `state_cid` for a message that does not
+  /// originate from a signed transaction, such as a simulated call.
+  ///
+  /// Storage writes are buffered in the in-memory HAMT and are only
+  /// persisted once [`System::flush_state`] is called, so dropping the
+  /// system without flushing discards all changes.
+  ///
+  /// The accounts in `access_list` and the keys it lists for this contract
+  /// start warm, they were paid for as part of the intrinsic gas.
+  pub fn with_context<RT: Runtime<BS>>(
+    state_cid: Cid,
+    runtime: &'r RT,
+    bridge: Address,
+    self_address: H160,
+    context: TransactionContext,
+    access_list: &[AccessListItem],
   ) -> anyhow::Result<Self> {
+    // the sender and the recipient of a transaction are always warm
+    let mut accessed_accounts = HashSet::from([self_address, context.tx_origin]);
+    accessed_accounts.extend(access_list.iter().map(|item| item.address));
+
+    // only the storage of this contract is reachable from here
+    let warm_keys = access_list
+      .iter()
+      .filter(|item| item.address == self_address)
+      .flat_map(|item| item.slots.iter())
+      .map(|slot| U256::from_big_endian(slot.as_bytes()))
+      .collect();
+
     Ok(Self {
-      context: TransactionContext {
-        tx_gas_price: tx.gas_price(),
-        tx_origin: tx.sender_address()?,
-        block_coinbase: H160::zero(),   // todo
-        block_number: 0,                // todo
-        block_timestamp: 0,             // todo
-        block_gas_limit: 30000000,      // todo
-        block_difficulty: U256::zero(), // todo
-        chain_id: tx.chain_id().unwrap_or_default().into(),
-        block_base_fee: U256::zero(), // todo
-      },
-      _bridge: bridge,
+      accessed_accounts: RefCell::new(accessed_accounts),
+      context,
+      bridge,
       self_address,
-      access_list: RefCell::new(HashSet::new()),
+      access_list: RefCell::new(warm_keys),
+      transient_storage: RefCell::new(HashMap::new()),
+      original_storage: RefCell::new(HashMap::new()),
+      selfdestruct_beneficiary: RefCell::new(None),
+      logs: RefCell::new(Vec::new()),
       state: RefCell::new(Hamt::load(&state_cid, runtime.store())?),
     })
   }
@@ -112,20 +197,67 @@ impl<'r, BS: Blockstore> System<'r, BS> {
       .map_err(|e| ActorError::illegal_state(e.to_string()))
   }
 
-  /// Check if an account exists.
-  pub fn account_exists(&self, _address: H160) -> bool {
-    todo!()
+  /// The beneficiary of the contract balance if SELFDESTRUCT was executed.
+  pub fn selfdestruct_beneficiary(&self) -> Option<H160> {
+    *self.selfdestruct_beneficiary.borrow()
+  }
+
+  /// Logs emitted so far, in emission order.
+  pub fn logs(&self) -> Vec<Log> {
+    self.logs.borrow().clone()
   }
 
-  /// Get value of a storage key.
+  /// Queries the bridge for the EVM account stored under `address`.
   ///
-  /// Returns `Ok(U256::zero())` if does not exist.
-  pub fn get_storage(&self, _address: H160, _key: U256) -> U256 {
-    todo!();
+  /// The bridge synthesizes an empty account for unknown addresses.
+  fn get_account(&self, address: H160) -> Result<EthereumAccount, StatusCode> {
+    let params = RawBytes::serialize(address)
+      .map_err(|e| StatusCode::InternalError(e.to_string()))?;
+
+    let receipt = fvm_sdk::send::send(
+      &self.bridge,
+      BRIDGE_GET_ACCOUNT_METHOD_NUM,
+      params,
+      BigInt::default(),
+    )
+    .map_err(|e| StatusCode::InternalError(e.to_string()))?;
+
+    if !receipt.exit_code.is_success() {
+      return Err(StatusCode::InternalError(format!(
+        "failed to query account {address:?}: {}",
+        receipt.exit_code
+      )));
+    }
+
+    receipt
+      .return_data
+      .deserialize()
+      .map_err(|e| StatusCode::InternalError(e.to_string()))
+  }
+}
+
+impl<'r, BS: Blockstore> Host for System<'r, BS> {
+  fn account_exists(&self, address: H160) -> Result<bool, StatusCode> {
+    Ok(!self.get_account(address)?.is_empty())
   }
 
-  /// Set value of a storage key.
-  pub fn set_storage(
+  fn get_storage(&self, address: H160, key: U256) -> Result<U256, StatusCode> {
+    if address != self.self_address {
+      return Err(cross_contract_storage());
+    }
+
+    Ok(
+      self
+        .state
+        .borrow()
+        .get(&key)
+        .map_err(|e| StatusCode::InternalError(e.to_string()))?
+        .cloned()
+        .unwrap_or_default(),
+    )
+  }
+
+  fn set_storage(
     &self,
     address: H160,
     key: U256,
@@ -135,104 +267,142 @@ impl<'r, BS: Blockstore> System<'r, BS> {
       "setting storage for {address:?} @ {key} to {value}"
     ));
     if address == self.self_address {
-      let mut storage_status = StorageStatus::Added;
-      let prev_value = self
+      let current = self
         .state
         .borrow()
         .get(&key)
         .map_err(|e| StatusCode::InternalError(e.to_string()))?
-        .cloned();
-
-      if let Some(v) = prev_value {
-        if v == value {
-          storage_status = StorageStatus::Unchanged;
-        } else {
-          storage_status = StorageStatus::Modified;
-        }
-      }
+        .cloned()
+        .unwrap_or_default();
+      let original = *self
+        .original_storage
+        .borrow_mut()
+        .entry(key)
+        .or_insert(current);
 
-      if value == U256::zero() {
-        self
-          .state
-          .borrow_mut()
-          .delete(&key)
-          .map_err(|e| StatusCode::InternalError(e.to_string()))?;
-        storage_status = StorageStatus::Deleted;
-      } else {
-        self
-          .state
-          .borrow_mut()
-          .set(key, value)
-          .map_err(|e| StatusCode::InternalError(e.to_string()))?;
+      let mut state = self.state.borrow_mut();
+      match value.is_zero() {
+        true => state.delete(&key).map(|_| ()),
+        false => state.set(key, value).map(|_| ()),
       }
+      .map_err(|e| StatusCode::InternalError(e.to_string()))?;
 
-      Ok(storage_status)
+      Ok(StorageStatus::new(original, current, value))
     } else {
-      unimplemented!("setting storage across contracts is not supported yet")
+      Err(cross_contract_storage())
     }
   }
 
-  /// Get balance of an account.
-  ///
-  /// Returns `Ok(0)` if account does not exist.
-  pub fn get_balance(&self, _address: H160) -> U256 {
-    todo!()
+  fn get_transient_storage(&self, address: H160, key: U256) -> U256 {
+    self
+      .transient_storage
+      .borrow()
+      .get(&(address, key))
+      .copied()
+      .unwrap_or_default()
   }
 
-  /// Get code size of an account.
-  ///
-  /// Returns `Ok(0)` if account does not exist.
-  pub fn get_code_size(&self, _address: H160) -> U256 {
-    todo!()
+  fn set_transient_storage(&self, address: H160, key: U256, value: U256) {
+    let mut storage = self.transient_storage.borrow_mut();
+    match value.is_zero() {
+      true => storage.remove(&(address, key)),
+      false => storage.insert((address, key), value),
+    };
   }
 
-  /// Get code hash of an account.
-  ///
-  /// Returns `Ok(0)` if account does not exist.
-  pub fn get_code_hash(&self, _address: H160) -> U256 {
-    todo!();
+  /// For EOAs linked to a FIL account this is the sum of the EVM-side
+  /// and the FIL balances, as reported by the bridge.
+  fn get_balance(&self, address: H160) -> Result<U256, StatusCode> {
+    Ok(self.get_account(address)?.balance)
   }
 
-  /// Copy code of an account.
-  ///
-  /// Returns `Ok(0)` if offset is invalid.
-  pub fn copy_code(&self, _address: H160, _offset: usize, _buffer: &mut [u8]) -> usize {
-    todo!()
+  fn get_code_size(&self, _address: H160) -> Result<U256, StatusCode> {
+    Err(unsupported("reading the code of accounts"))
   }
 
-  /// Self-destruct account.
-  pub fn selfdestruct(&self, _address: H160, _beneficiary: H160) {
-    todo!()
+  fn get_code_hash(&self, _address: H160) -> Result<U256, StatusCode> {
+    Err(unsupported("reading the code of accounts"))
+  }
+
+  fn copy_code(
+    &self,
+    _address: H160,
+    _offset: usize,
+    _buffer: &mut [u8],
+  ) -> Result<usize, StatusCode> {
+    Err(unsupported("reading the code of accounts"))
   }
 
-  /// Call to another account.
-  pub fn call(&self, _msg: Call) -> Output {
-    todo!();
+  /// The account is only marked for deletion here. Its balance is moved
+  /// to the beneficiary and the account is removed by the bridge once the
+  /// transaction has finished executing.
+  fn selfdestruct(&self, address: H160, beneficiary: H160) -> Result<(), StatusCode> {
+    if address != self.self_address {
+      return Err(StatusCode::InternalError(
+        "self-destructing other contracts is not supported".into(),
+      ));
+    }
+    self.selfdestruct_beneficiary.replace(Some(beneficiary));
+    Ok(())
   }
 
-  /// Get block hash.
+  /// Fails without consuming any gas, the caller then observes a failed
+  /// call just like one that ran out of gas.
   ///
-  /// Returns `Ok(U256::zero())` if block does not exist.
-  pub fn get_block_hash(&self, _block_number: u64) -> U256 {
-    todo!();
+  /// No frame runs, so there is nothing to roll back. Once calls are
+  /// supported they need a per-frame journal like the one of the in-memory
+  /// test host, covering transient storage, storage, the accessed accounts
+  /// and keys, and logs.
+  fn call(&self, _msg: Call) -> Output {
+    Output {
+      status_code: unsupported("calls to other contracts"),
+      gas_left: 0,
+      gas_refund: 0,
+      output_data: Bytes::new(),
+      reverted: false,
+    }
   }
 
-  /// Emit a log.
-  pub fn emit_log(&self, _address: H160, _data: Bytes, _topics: &[U256]) {
-    todo!();
+  /// The hash of an epoch is the digest of its tipset CID. Only the last
+  /// [`BLOCKHASH_WINDOW`] epochs before the current one are available, and
+  /// epochs without a tipset (null rounds) have no hash either.
+  fn get_block_hash(&self, block_number: u64) -> U256 {
+    if !in_blockhash_window(self.context.block_number, block_number) {
+      return U256::zero();
+    }
+
+    match fvm_sdk::network::tipset_cid(block_number as ChainEpoch) {
+      Ok(cid) => match cid.hash().digest() {
+        digest if digest.len() == 32 => U256::from_big_endian(digest),
+        _ => U256::from_big_endian(&Keccak256::digest(cid.to_bytes())),
+      },
+      Err(_) => U256::zero(),
+    }
   }
 
-  /// Mark account as warm, return previous access status.
-  ///
-  /// Returns `Ok(AccessStatus::Cold)` if account does not exist.
-  pub fn access_account(&self, _address: H160) -> AccessStatus {
-    todo!();
+  fn emit_log(
+    &self,
+    address: H160,
+    data: Bytes,
+    topics: &[U256],
+  ) -> Result<(), StatusCode> {
+    self.logs.borrow_mut().push(Log {
+      address,
+      data,
+      topics: topics.to_vec(),
+    });
+    Ok(())
   }
 
-  /// Mark storage key as warm, return previous access status.
-  ///
-  /// Returns `Ok(AccessStatus::Cold)` if account does not exist.
-  pub fn access_storage(&self, address: H160, key: U256) -> AccessStatus {
+  fn access_account(&self, address: H160) -> AccessStatus {
+    if self.accessed_accounts.borrow_mut().insert(address) {
+      AccessStatus::Cold
+    } else {
+      AccessStatus::Warm
+    }
+  }
+
+  fn access_storage(&self, address: H160, key: U256) -> AccessStatus {
     if address == self.self_address {
       if self.access_list.borrow().contains(&key) {
         AccessStatus::Warm
@@ -241,12 +411,44 @@ impl<'r, BS: Blockstore> System<'r, BS> {
         AccessStatus::Cold
       }
     } else {
-      unimplemented!("cross-contract storage access is not supported yet");
+      // get_storage and set_storage reject such keys before they are used
+      AccessStatus::Cold
     }
   }
 
-  /// Return context information about the current transaction and current block
-  pub fn transaction_context(&self) -> &TransactionContext {
+  fn transaction_context(&self) -> &TransactionContext {
     &self.context
   }
 }
+
+/// Error for a feature of the EVM that the FVM system does not provide yet.
+fn unsupported(feature: &str) -> StatusCode {
+  StatusCode::InternalError(format!("{feature} is not supported yet"))
+}
+
+fn cross_contract_storage() -> StatusCode {
+  unsupported("accessing the storage of other contracts")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn blockhash_window() {
+    assert!(!in_blockhash_window(1000, 1000));
+    assert!(!in_blockhash_window(1000, 1001));
+    assert!(in_blockhash_window(1000, 999));
+    assert!(in_blockhash_window(1000, 744));
+    assert!(!in_blockhash_window(1000, 743));
+    assert!(in_blockhash_window(10, 0));
+    assert!(!in_blockhash_window(0, 0));
+  }
+
+  #[test]
+  fn epoch_timestamps() {
+    assert_eq!(epoch_to_timestamp(0), GENESIS_TIMESTAMP);
+    assert_eq!(epoch_to_timestamp(2), GENESIS_TIMESTAMP + 60);
+    assert_eq!(epoch_to_timestamp(-1), GENESIS_TIMESTAMP);
+  }
+}
@@ -0,0 +1,858 @@
+//! An in-memory [`Host`] for running the interpreter natively.
+//!
+//! Unlike [`System`](crate::System), which needs a running FVM to reach
+//! the contract storage and the bridge actor, [`InMemoryHost`] keeps all
+//! accounts in memory. This makes it possible to exercise `execute` in
+//! plain `cargo test` without building any wasm bundles.
+
+use {
+  crate::{
+    bytecode::Bytecode,
+    execution::{execute, ExecutionState},
+    host::{AccessStatus, Call, Host, Log, StorageStatus, TransactionContext},
+    message::Message,
+    output::StatusCode,
+    revision::Revision,
+    system::BLOCK_GAS_LIMIT,
+    Output,
+    H160,
+    U256,
+  },
+  bytes::Bytes,
+  sha3::{Digest, Keccak256},
+  std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+  },
+};
+
+/// State of an account held by [`InMemoryHost`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InMemoryAccount {
+  pub nonce: u64,
+  pub balance: U256,
+  pub code: Bytes,
+  pub storage: HashMap<U256, U256>,
+}
+
+impl InMemoryAccount {
+  /// Empty accounts are considered non-existent (EIP-161).
+  pub fn is_empty(&self) -> bool {
+    self.nonce == 0 && self.balance.is_zero() && self.code.is_empty()
+  }
+}
+
+/// A change made through the [`Host`] interface, recorded so that it can
+/// be undone when the call frame that made it does not succeed.
+enum JournalEntry {
+  /// The account did not exist before.
+  AccountCreated(H160),
+  Balance(H160, U256),
+  Storage(H160, U256, U256),
+  /// The slot was written for the first time in this transaction.
+  OriginalStorage(H160, U256),
+  TransientStorage(H160, U256, U256),
+  AccessedAccount(H160),
+  AccessedStorage(H160, U256),
+  Log,
+  Selfdestruct,
+}
+
+/// A [`Host`] backed by hash maps, with a configurable block context.
+///
+/// Accounts are deployed and inspected through inherent methods, while
+/// the interpreter reaches them through the [`Host`] implementation.
+///
+/// Changes made through the [`Host`] interface are journaled, so a call
+/// frame that fails undoes exactly its own changes and those of the frames
+/// it called, leaving the changes of its caller in place.
+pub struct InMemoryHost {
+  accounts: RefCell<HashMap<H160, InMemoryAccount>>,
+  transient_storage: RefCell<HashMap<(H160, U256), U256>>,
+  /// Values of the slots written in the current transaction, as they
+  /// were before the first write.
+  original_storage: RefCell<HashMap<(H160, U256), U256>>,
+  logs: RefCell<Vec<Log>>,
+  selfdestructs: RefCell<Vec<(H160, H160)>>,
+  accessed_accounts: RefCell<HashSet<H160>>,
+  accessed_storage: RefCell<HashSet<(H160, U256)>>,
+  journal: RefCell<Vec<JournalEntry>>,
+  block_hashes: HashMap<u64, U256>,
+  context: TransactionContext,
+}
+
+impl Default for InMemoryHost {
+  fn default() -> Self {
+    Self::new(TransactionContext {
+      tx_gas_price: U256::zero(),
+      tx_origin: H160::zero(),
+      block_coinbase: H160::zero(),
+      block_number: 0,
+      block_timestamp: 0,
+      block_gas_limit: BLOCK_GAS_LIMIT,
+      block_difficulty: U256::zero(),
+      chain_id: U256::zero(),
+      block_base_fee: U256::zero(),
+      revision: Revision::LATEST,
+    })
+  }
+}
+
+impl InMemoryHost {
+  pub fn new(context: TransactionContext) -> Self {
+    Self {
+      accounts: RefCell::new(HashMap::new()),
+      transient_storage: RefCell::new(HashMap::new()),
+      original_storage: RefCell::new(HashMap::new()),
+      logs: RefCell::new(Vec::new()),
+      selfdestructs: RefCell::new(Vec::new()),
+      accessed_accounts: RefCell::new(HashSet::new()),
+      accessed_storage: RefCell::new(HashSet::new()),
+      journal: RefCell::new(Vec::new()),
+      block_hashes: HashMap::new(),
+      context,
+    }
+  }
+
+  /// The block and transaction context observed by the interpreter.
+  pub fn context_mut(&mut self) -> &mut TransactionContext {
+    &mut self.context
+  }
+
+  /// Sets the hash returned by BLOCKHASH for `block_number`.
+  pub fn set_block_hash(&mut self, block_number: u64, hash: U256) {
+    self.block_hashes.insert(block_number, hash);
+  }
+
+  /// Replaces the account stored under `address`.
+  pub fn insert_account(&self, address: H160, account: InMemoryAccount) {
+    self.accounts.borrow_mut().insert(address, account);
+  }
+
+  /// Sets the code of `address`, creating the account if needed.
+  pub fn deploy(&self, address: H160, code: impl Into<Bytes>) {
+    self.accounts.borrow_mut().entry(address).or_default().code = code.into();
+  }
+
+  /// Sets the balance of `address`, creating the account if needed.
+  pub fn set_balance(&self, address: H160, balance: U256) {
+    self
+      .accounts
+      .borrow_mut()
+      .entry(address)
+      .or_default()
+      .balance = balance;
+  }
+
+  /// Writes a storage slot of `address` without going through SSTORE.
+  pub fn set_storage_value(&self, address: H160, key: U256, value: U256) {
+    let mut accounts = self.accounts.borrow_mut();
+    let storage = &mut accounts.entry(address).or_default().storage;
+    match value.is_zero() {
+      true => storage.remove(&key),
+      false => storage.insert(key, value),
+    };
+  }
+
+  /// Returns a copy of the account stored under `address`, if any.
+  pub fn account(&self, address: H160) -> Option<InMemoryAccount> {
+    self.accounts.borrow().get(&address).cloned()
+  }
+
+  /// Returns a copy of all accounts, including empty ones.
+  pub fn accounts(&self) -> HashMap<H160, InMemoryAccount> {
+    self.accounts.borrow().clone()
+  }
+
+  /// Removes the account stored under `address`, returning it if present.
+  pub fn remove_account(&self, address: H160) -> Option<InMemoryAccount> {
+    self.accounts.borrow_mut().remove(&address)
+  }
+
+  /// Returns the value of a storage slot, zero if it was never written.
+  pub fn storage_value(&self, address: H160, key: U256) -> U256 {
+    self
+      .accounts
+      .borrow()
+      .get(&address)
+      .and_then(|a| a.storage.get(&key).copied())
+      .unwrap_or_default()
+  }
+
+  /// Logs emitted so far, in emission order.
+  pub fn logs(&self) -> Vec<Log> {
+    self.logs.borrow().clone()
+  }
+
+  /// Pairs of self-destructed accounts and their beneficiaries.
+  pub fn selfdestructs(&self) -> Vec<(H160, H160)> {
+    self.selfdestructs.borrow().clone()
+  }
+
+  /// Discards the state that only lives for the duration of a
+  /// transaction: transient storage, the original values of written
+  /// slots and the accounts and storage keys accessed so far.
+  pub fn end_transaction(&self) {
+    self.transient_storage.borrow_mut().clear();
+    self.original_storage.borrow_mut().clear();
+    self.accessed_accounts.borrow_mut().clear();
+    self.accessed_storage.borrow_mut().clear();
+    self.journal.borrow_mut().clear();
+  }
+
+  /// Runs the code deployed at the recipient of `message`.
+  ///
+  /// The sender and the recipient start warm, as they would at the
+  /// beginning of a transaction (EIP-2929).
+  pub fn execute(&self, message: &Message) -> Result<Output, StatusCode> {
+    let code = self.account(message.recipient).unwrap_or_default().code;
+    let bytecode = Bytecode::new(&code)?;
+
+    for address in [message.sender, message.recipient, self.context.tx_origin] {
+      self.access_account(address);
+    }
+
+    let mut state = ExecutionState::new(message);
+    execute(&bytecode, &mut state, self)
+  }
+
+  /// Moves the value attached to `message` from the sender to the recipient.
+  fn transfer(&self, message: &Message) -> Result<(), StatusCode> {
+    if message.value.is_zero() {
+      return Ok(());
+    }
+
+    let sender = self
+      .get_balance(message.sender)?
+      .checked_sub(message.value)
+      .ok_or(StatusCode::InsufficientBalance)?;
+    self.write_balance(message.sender, sender);
+    let recipient = self.get_balance(message.recipient)?;
+    self.write_balance(message.recipient, recipient + message.value);
+    Ok(())
+  }
+
+  /// Sets the balance of `address` on behalf of the running frame.
+  fn write_balance(&self, address: H160, balance: U256) {
+    let previous = self.accounts.borrow().get(&address).map(|a| a.balance);
+    self.record(match previous {
+      Some(previous) => JournalEntry::Balance(address, previous),
+      None => JournalEntry::AccountCreated(address),
+    });
+    self.set_balance(address, balance);
+  }
+
+  fn write_transient_storage(&self, address: H160, key: U256, value: U256) {
+    let mut storage = self.transient_storage.borrow_mut();
+    match value.is_zero() {
+      true => storage.remove(&(address, key)),
+      false => storage.insert((address, key), value),
+    };
+  }
+
+  fn record(&self, entry: JournalEntry) {
+    self.journal.borrow_mut().push(entry);
+  }
+
+  /// Undoes the changes recorded since the journal had `checkpoint`
+  /// entries, newest first.
+  fn revert_to(&self, checkpoint: usize) {
+    let entries = self.journal.borrow_mut().split_off(checkpoint);
+    for entry in entries.into_iter().rev() {
+      match entry {
+        JournalEntry::AccountCreated(address) => {
+          self.accounts.borrow_mut().remove(&address);
+        }
+        JournalEntry::Balance(address, balance) => self.set_balance(address, balance),
+        JournalEntry::Storage(address, key, value) => {
+          self.set_storage_value(address, key, value)
+        }
+        JournalEntry::OriginalStorage(address, key) => {
+          self.original_storage.borrow_mut().remove(&(address, key));
+        }
+        JournalEntry::TransientStorage(address, key, value) => {
+          self.write_transient_storage(address, key, value)
+        }
+        JournalEntry::AccessedAccount(address) => {
+          self.accessed_accounts.borrow_mut().remove(&address);
+        }
+        JournalEntry::AccessedStorage(address, key) => {
+          self.accessed_storage.borrow_mut().remove(&(address, key));
+        }
+        JournalEntry::Log => {
+          self.logs.borrow_mut().pop();
+        }
+        JournalEntry::Selfdestruct => {
+          self.selfdestructs.borrow_mut().pop();
+        }
+      }
+    }
+  }
+}
+
+impl Host for InMemoryHost {
+  fn account_exists(&self, address: H160) -> Result<bool, StatusCode> {
+    Ok(self.account(address).map_or(false, |a| !a.is_empty()))
+  }
+
+  fn get_storage(&self, address: H160, key: U256) -> Result<U256, StatusCode> {
+    Ok(self.storage_value(address, key))
+  }
+
+  fn set_storage(
+    &self,
+    address: H160,
+    key: U256,
+    value: U256,
+  ) -> Result<StorageStatus, StatusCode> {
+    let current = self.storage_value(address, key);
+    let original = *self
+      .original_storage
+      .borrow_mut()
+      .entry((address, key))
+      .or_insert_with(|| {
+        self.record(JournalEntry::OriginalStorage(address, key));
+        current
+      });
+
+    if !self.accounts.borrow().contains_key(&address) {
+      self.record(JournalEntry::AccountCreated(address));
+    }
+    self.record(JournalEntry::Storage(address, key, current));
+    self.set_storage_value(address, key, value);
+
+    Ok(StorageStatus::new(original, current, value))
+  }
+
+  fn get_transient_storage(&self, address: H160, key: U256) -> U256 {
+    self
+      .transient_storage
+      .borrow()
+      .get(&(address, key))
+      .copied()
+      .unwrap_or_default()
+  }
+
+  fn set_transient_storage(&self, address: H160, key: U256, value: U256) {
+    let previous = self.get_transient_storage(address, key);
+    self.record(JournalEntry::TransientStorage(address, key, previous));
+    self.write_transient_storage(address, key, value);
+  }
+
+  fn get_balance(&self, address: H160) -> Result<U256, StatusCode> {
+    Ok(self.account(address).unwrap_or_default().balance)
+  }
+
+  fn get_code_size(&self, address: H160) -> Result<U256, StatusCode> {
+    Ok(self.account(address).unwrap_or_default().code.len().into())
+  }
+
+  fn get_code_hash(&self, address: H160) -> Result<U256, StatusCode> {
+    Ok(match self.account(address) {
+      Some(account) if !account.is_empty() => {
+        U256::from_big_endian(&Keccak256::digest(&account.code))
+      }
+      _ => U256::zero(),
+    })
+  }
+
+  fn copy_code(
+    &self,
+    address: H160,
+    offset: usize,
+    buffer: &mut [u8],
+  ) -> Result<usize, StatusCode> {
+    let code = self.account(address).unwrap_or_default().code;
+    let code = code.get(offset..).unwrap_or_default();
+    let len = code.len().min(buffer.len());
+    buffer[..len].copy_from_slice(&code[..len]);
+    Ok(len)
+  }
+
+  fn selfdestruct(&self, address: H160, beneficiary: H160) -> Result<(), StatusCode> {
+    self.selfdestructs.borrow_mut().push((address, beneficiary));
+    self.record(JournalEntry::Selfdestruct);
+    Ok(())
+  }
+
+  /// Runs the code of the callee. All state changes made by the callee,
+  /// including transient storage, logs and the accounts and storage keys
+  /// it warmed up, are rolled back from the journal if it does not
+  /// succeed. Creations are not supported.
+  fn call(&self, msg: Call) -> Output {
+    let message = match msg {
+      Call::Call(message) => message,
+      Call::Create(message) => {
+        return Output {
+          status_code: StatusCode::InternalError("CREATE is not supported".into()),
+          gas_left: 0,
+          gas_refund: 0,
+          output_data: Bytes::new(),
+          reverted: false,
+        }
+      }
+    };
+
+    let checkpoint = self.journal.borrow().len();
+
+    let result = self.transfer(message).and_then(|_| self.execute(message));
+    let output = result.unwrap_or_else(|status_code| Output {
+      status_code,
+      gas_left: 0,
+      gas_refund: 0,
+      output_data: Bytes::new(),
+      reverted: false,
+    });
+
+    if output.reverted || output.status_code != StatusCode::Success {
+      self.revert_to(checkpoint);
+    }
+
+    output
+  }
+
+  fn get_block_hash(&self, block_number: u64) -> U256 {
+    self
+      .block_hashes
+      .get(&block_number)
+      .copied()
+      .unwrap_or_default()
+  }
+
+  fn emit_log(
+    &self,
+    address: H160,
+    data: Bytes,
+    topics: &[U256],
+  ) -> Result<(), StatusCode> {
+    self.logs.borrow_mut().push(Log {
+      address,
+      data,
+      topics: topics.to_vec(),
+    });
+    self.record(JournalEntry::Log);
+    Ok(())
+  }
+
+  fn access_account(&self, address: H160) -> AccessStatus {
+    match self.accessed_accounts.borrow_mut().insert(address) {
+      true => {
+        self.record(JournalEntry::AccessedAccount(address));
+        AccessStatus::Cold
+      }
+      false => AccessStatus::Warm,
+    }
+  }
+
+  fn access_storage(&self, address: H160, key: U256) -> AccessStatus {
+    match self.accessed_storage.borrow_mut().insert((address, key)) {
+      true => {
+        self.record(JournalEntry::AccessedStorage(address, key));
+        AccessStatus::Cold
+      }
+      false => AccessStatus::Warm,
+    }
+  }
+
+  fn transaction_context(&self) -> &TransactionContext {
+    &self.context
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::{message::CallKind, opcode::OpCode},
+    hex_literal::hex,
+  };
+
+  fn message(recipient: H160, gas: i64) -> Message {
+    Message {
+      kind: CallKind::Call,
+      is_static: false,
+      depth: 0,
+      gas,
+      recipient,
+      sender: H160::repeat_byte(0xaa),
+      input_data: Bytes::new(),
+      value: U256::zero(),
+    }
+  }
+
+  #[test]
+  fn sstore_writes_storage() {
+    let host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+
+    // PUSH1 0x2a PUSH1 0x01 SSTORE
+    host.deploy(contract, hex!("602a600155").to_vec());
+
+    let output = host.execute(&message(contract, 100_000)).unwrap();
+    assert_eq!(output.status_code, StatusCode::Success);
+    assert_eq!(output.gas_left, 100_000 - 2 * 3 - 2100 - 20000);
+    assert_eq!(host.storage_value(contract, 1.into()), 0x2a.into());
+    assert_eq!(host.access_storage(contract, 1.into()), AccessStatus::Warm);
+  }
+
+  #[test]
+  fn charges_static_prices() {
+    let host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+
+    // PUSH1 1 PUSH1 2 ADD POP
+    host.deploy(contract, hex!("600160020150").to_vec());
+
+    let output = host.execute(&message(contract, 100_000)).unwrap();
+    assert_eq!(output.gas_left, 100_000 - 3 - 3 - 3 - 2);
+
+    // the last instruction cannot be paid for
+    assert_eq!(
+      host.execute(&message(contract, 10)),
+      Err(StatusCode::OutOfGas)
+    );
+
+    // JUMPDEST PUSH1 0 JUMP loops until the gas runs out
+    host.deploy(contract, hex!("5b600056").to_vec());
+    assert_eq!(
+      host.execute(&message(contract, 100_000)),
+      Err(StatusCode::OutOfGas)
+    );
+  }
+
+  #[test]
+  fn unsupported_instructions_fail() {
+    let contract = H160::repeat_byte(0x01);
+
+    // EXTCODESIZE, EXTCODEHASH, EXTCODECOPY, CREATE and CALL, each with
+    // its arguments pushed by PUSH0, ADDRESS and GAS
+    for code in [
+      &hex!("303b")[..],
+      &hex!("303f"),
+      &hex!("5f5f5f303c"),
+      &hex!("5f5f5ff0"),
+      &hex!("5f5f5f5f5f305af1"),
+    ] {
+      let host = InMemoryHost::default();
+      host.deploy(contract, code.to_vec());
+      assert!(matches!(
+        host.execute(&message(contract, 100_000)),
+        Err(StatusCode::InternalError(_))
+      ));
+    }
+  }
+
+  #[test]
+  fn selfdestruct_records_beneficiary() {
+    let host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+
+    // CALLER SELFDESTRUCT
+    host.deploy(contract, hex!("33ff").to_vec());
+
+    let output = host.execute(&message(contract, 100_000)).unwrap();
+    assert_eq!(output.status_code, StatusCode::Success);
+    assert_eq!(host.selfdestructs(), vec![(
+      contract,
+      H160::repeat_byte(0xaa)
+    )]);
+
+    // a static call may not destroy the contract
+    let host = InMemoryHost::default();
+    host.deploy(contract, hex!("33ff").to_vec());
+    let static_call = Message {
+      is_static: true,
+      ..message(contract, 100_000)
+    };
+    assert_eq!(
+      host.execute(&static_call),
+      Err(StatusCode::StaticModeViolation)
+    );
+    assert!(host.selfdestructs().is_empty());
+  }
+
+  #[test]
+  fn sstore_refunds_are_capped() {
+    let host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+
+    // PUSH1 0x2a PUSH1 0x01 SSTORE PUSH1 0 PUSH1 0x01 SSTORE
+    host.deploy(contract, hex!("602a600155600060015500").to_vec());
+
+    // clearing a slot added in the same transaction refunds all but the
+    // cost of a warm write
+    let output = host.execute(&message(contract, 100_000)).unwrap();
+    let gas_used = 4 * 3 + 2100 + 20000 + 100;
+    assert_eq!(output.gas_left, 100_000 - gas_used);
+    assert_eq!(output.gas_refund, 19900);
+    assert_eq!(host.storage_value(contract, 1.into()), U256::zero());
+
+    // the transaction gets up to a fifth of all the gas it used back,
+    // intrinsic gas included (EIP-3529)
+    let tx_gas_used = 21000 + gas_used as u64;
+    let refund = Revision::London.capped_refund(output.gas_refund, tx_gas_used);
+    assert_eq!(refund, tx_gas_used / 5);
+
+    // before London half of the gas used could be refunded
+    let mut host = InMemoryHost::default();
+    host.deploy(contract, hex!("602a600155600060015500").to_vec());
+    host.context_mut().revision = Revision::Berlin;
+    let output = host.execute(&message(contract, 100_000)).unwrap();
+    assert_eq!(output.gas_refund, 19900);
+    let refund = Revision::Berlin.capped_refund(output.gas_refund, tx_gas_used);
+    assert_eq!(refund, 19900);
+  }
+
+  #[test]
+  fn storage_status_tracks_original_value() {
+    let host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+    host.set_storage_value(contract, 1.into(), 1.into());
+
+    let set = |key: u64, value: u64| {
+      host
+        .set_storage(contract, key.into(), value.into())
+        .unwrap()
+    };
+    assert_eq!(set(1, 1), StorageStatus::Unchanged);
+    assert_eq!(set(1, 2), StorageStatus::Modified);
+    assert_eq!(set(1, 3), StorageStatus::ModifiedAgain);
+    assert_eq!(set(1, 0), StorageStatus::ModifiedDeleted);
+    assert_eq!(set(1, 2), StorageStatus::DeletedAdded);
+    assert_eq!(set(1, 1), StorageStatus::ModifiedRestored);
+    assert_eq!(set(1, 0), StorageStatus::Deleted);
+    assert_eq!(set(1, 1), StorageStatus::DeletedRestored);
+
+    assert_eq!(set(2, 1), StorageStatus::Added);
+    assert_eq!(set(2, 2), StorageStatus::ModifiedAgain);
+    assert_eq!(set(2, 0), StorageStatus::AddedDeleted);
+
+    // the next transaction starts from the values written by this one
+    host.end_transaction();
+    assert_eq!(set(1, 2), StorageStatus::Modified);
+    assert_eq!(set(2, 1), StorageStatus::Added);
+  }
+
+  #[test]
+  fn sload_reads_storage() {
+    let host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+    host.set_storage_value(contract, 1.into(), 0x2a.into());
+
+    // PUSH1 0x01 SLOAD PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    host.deploy(contract, hex!("60015460005260206000f3").to_vec());
+
+    let output = host.execute(&message(contract, 100_000)).unwrap();
+    assert_eq!(U256::from_big_endian(&output.output_data), 0x2a.into());
+    // the slot is cold, memory expansion to one word costs 3
+    assert_eq!(output.gas_left, 100_000 - 4 * 3 - 2100 - 3 - 3);
+  }
+
+  #[test]
+  fn emits_logs() {
+    let host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+
+    // PUSH1 0x2a PUSH1 0 MSTORE PUSH1 0xbb PUSH1 32 PUSH1 0 LOG1
+    host.deploy(contract, hex!("602a60005260bb60206000a1").to_vec());
+
+    let output = host.execute(&message(contract, 100_000)).unwrap();
+    assert_eq!(output.status_code, StatusCode::Success);
+
+    let logs = host.logs();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].address, contract);
+    assert_eq!(logs[0].topics, vec![U256::from(0xbb)]);
+    assert_eq!(U256::from_big_endian(&logs[0].data), 0x2a.into());
+  }
+
+  #[test]
+  fn returns_balance() {
+    let host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+    host.set_balance(contract, 1234.into());
+
+    // SELFBALANCE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    host.deploy(contract, hex!("4760005260206000f3").to_vec());
+
+    let output = host.execute(&message(contract, 100_000)).unwrap();
+    assert!(!output.reverted);
+    assert_eq!(U256::from_big_endian(&output.output_data), 1234.into());
+  }
+
+  #[test]
+  fn block_context() {
+    let mut host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+    host.context_mut().block_number = 100;
+    host.set_block_hash(99, 0xabcd.into());
+
+    // PUSH1 99 BLOCKHASH PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    host.deploy(contract, hex!("60634060005260206000f3").to_vec());
+
+    let output = host.execute(&message(contract, 100_000)).unwrap();
+    assert_eq!(U256::from_big_endian(&output.output_data), 0xabcd.into());
+  }
+
+  #[test]
+  fn opcodes_follow_revision() {
+    let mut host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+    host.context_mut().block_base_fee = 7.into();
+
+    // BASEFEE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    host.deploy(contract, hex!("4860005260206000f3").to_vec());
+
+    let output = host.execute(&message(contract, 100_000)).unwrap();
+    assert_eq!(U256::from_big_endian(&output.output_data), 7.into());
+
+    // BASEFEE was introduced in London
+    host.context_mut().revision = Revision::Berlin;
+    assert_eq!(
+      host.execute(&message(contract, 100_000)),
+      Err(StatusCode::UndefinedInstruction)
+    );
+  }
+
+  #[test]
+  fn prices_follow_revision() {
+    // repriced by EIP-2929 in Berlin
+    assert_eq!(OpCode::SLOAD.price, 800);
+    for revision in [Revision::Berlin, Revision::Cancun] {
+      let sload = OpCode::lookup(0x54, revision).unwrap();
+      assert_eq!(sload.price, 100);
+    }
+
+    let mut host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+    host.context_mut().revision = Revision::Berlin;
+
+    // PUSH1 0 SLOAD PUSH1 0 SLOAD
+    host.deploy(contract, hex!("600054600054").to_vec());
+
+    // a cold and a warm access
+    let output = host.execute(&message(contract, 100_000)).unwrap();
+    assert_eq!(output.gas_left, 100_000 - 2 * 3 - 2100 - 100);
+  }
+
+  #[test]
+  fn push0_and_mcopy() {
+    let mut host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+
+    // PUSH1 0x2a PUSH0 MSTORE PUSH1 32 PUSH0 PUSH1 32 MCOPY
+    // PUSH1 32 PUSH1 32 RETURN
+    host.deploy(contract, hex!("602a5f5260205f60205e60206020f3").to_vec());
+
+    let output = host.execute(&message(contract, 100_000)).unwrap();
+    assert_eq!(U256::from_big_endian(&output.output_data), 0x2a.into());
+    // MCOPY pays 3 for the copied word and 3 for expanding memory
+    assert_eq!(output.gas_left, 100_000 - 34);
+
+    host.context_mut().revision = Revision::London;
+    assert_eq!(
+      host.execute(&message(contract, 100_000)),
+      Err(StatusCode::UndefinedInstruction)
+    );
+  }
+
+  #[test]
+  fn transient_storage() {
+    let host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+    let reverting = H160::repeat_byte(0x02);
+
+    // PUSH1 0x2a PUSH1 1 TSTORE PUSH1 1 TLOAD PUSH1 0 MSTORE
+    // PUSH1 32 PUSH1 0 RETURN
+    host.deploy(contract, hex!("602a60015d60015c60005260206000f3").to_vec());
+    // PUSH1 0x2a PUSH1 1 TSTORE PUSH1 0 PUSH1 0 REVERT
+    host.deploy(reverting, hex!("602a60015d60006000fd").to_vec());
+
+    let output = host.call(Call::Call(&message(contract, 100_000)));
+    assert_eq!(U256::from_big_endian(&output.output_data), 0x2a.into());
+    assert_eq!(host.get_transient_storage(contract, 1.into()), 0x2a.into());
+
+    // writes of a failed call frame are reverted
+    assert!(host.call(Call::Call(&message(reverting, 100_000))).reverted);
+    assert_eq!(
+      host.get_transient_storage(reverting, 1.into()),
+      U256::zero()
+    );
+
+    // nothing outlives the transaction
+    host.end_transaction();
+    assert_eq!(host.get_transient_storage(contract, 1.into()), U256::zero());
+  }
+
+  #[test]
+  fn failed_calls_roll_back() {
+    let host = InMemoryHost::default();
+    let sender = H160::repeat_byte(0xaa);
+    let contract = H160::repeat_byte(0x01);
+    host.set_balance(sender, 10.into());
+
+    // PUSH1 0x2a PUSH1 0x01 SSTORE PUSH1 0 PUSH1 0 REVERT
+    host.deploy(contract, hex!("602a60015560006000fd").to_vec());
+
+    let output = host.call(Call::Call(&Message {
+      value: 5.into(),
+      ..message(contract, 100_000)
+    }));
+    assert!(output.reverted);
+    assert_eq!(host.storage_value(contract, 1.into()), U256::zero());
+    assert_eq!(host.get_balance(sender).unwrap(), 10.into());
+    assert_eq!(host.get_balance(contract).unwrap(), U256::zero());
+  }
+
+  #[test]
+  fn failed_calls_cool_down_accessed_state() {
+    let host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+    let other = H160::repeat_byte(0x02);
+
+    // PUSH20 <other> BALANCE POP PUSH1 0x01 SLOAD POP PUSH1 0 PUSH1 0 REVERT
+    let mut code = hex!("73").to_vec();
+    code.extend_from_slice(other.as_bytes());
+    code.extend_from_slice(&hex!("31506001545060006000fd"));
+    host.deploy(contract, code);
+
+    assert!(host.call(Call::Call(&message(contract, 100_000))).reverted);
+
+    // the reverted frame warmed both, the caller sees them cold (EIP-2929)
+    assert_eq!(host.access_account(other), AccessStatus::Cold);
+    assert_eq!(host.access_storage(contract, 1.into()), AccessStatus::Cold);
+  }
+
+  #[test]
+  fn failed_frames_undo_only_their_changes() {
+    let host = InMemoryHost::default();
+    let contract = H160::repeat_byte(0x01);
+    let other = H160::repeat_byte(0x02);
+
+    // PUSH1 1 PUSH1 1 SSTORE PUSH1 1 PUSH1 1 TSTORE PUSH1 0 PUSH1 0 LOG0
+    host.deploy(contract, hex!("6001600155600160015d60006000a000").to_vec());
+    let output = host.call(Call::Call(&message(contract, 100_000)));
+    assert_eq!(output.status_code, StatusCode::Success);
+
+    // the same writes with 2, then PUSH20 <other> BALANCE POP and
+    // PUSH1 0 PUSH1 0 REVERT
+    let mut code = hex!("6002600155600260015d60006000a073").to_vec();
+    code.extend_from_slice(other.as_bytes());
+    code.extend_from_slice(&hex!("315060006000fd"));
+    host.deploy(contract, code);
+    assert!(host.call(Call::Call(&message(contract, 100_000))).reverted);
+
+    // the changes of the first frame survive the second one
+    assert_eq!(host.storage_value(contract, 1.into()), 1.into());
+    assert_eq!(host.get_transient_storage(contract, 1.into()), 1.into());
+    assert_eq!(host.logs().len(), 1);
+    assert_eq!(host.access_account(other), AccessStatus::Cold);
+    assert_eq!(host.access_storage(contract, 1.into()), AccessStatus::Warm);
+
+    // including the value the slot had when the transaction started
+    assert_eq!(
+      host.set_storage(contract, 1.into(), 3.into()),
+      Ok(StorageStatus::ModifiedAgain)
+    );
+  }
+}
@@ -1,20 +1,75 @@
 use {
-  crate::{H160, H256, U256},
+  crate::{bytecode::INITCODE_WORD_COST, H160, H256, U256},
   bytes::Bytes,
   fil_actors_runtime::ActorError,
   fvm_shared::crypto::signature::SECP_PUB_LEN,
   rlp::{DecoderError, Rlp, RlpStream},
+  serde_tuple::{Deserialize_tuple, Serialize_tuple},
   sha3::{Digest, Keccak256},
   std::{fmt::Debug, ops::Deref},
 };
 
+/// Gas paid by every transaction before any code is executed.
+pub const TX_BASE_GAS: u64 = 21000;
+
+/// Additional gas paid by transactions that create a contract.
+pub const TX_CREATE_GAS: u64 = 32000;
+
+/// Gas paid for every zero byte of transaction input.
+pub const TX_DATA_ZERO_GAS: u64 = 4;
+
+/// Gas paid for every non-zero byte of transaction input (EIP-2028).
+pub const TX_DATA_NON_ZERO_GAS: u64 = 16;
+
+/// Gas paid for every address in the access list (EIP-2930).
+pub const TX_ACCESS_LIST_ADDRESS_GAS: u64 = 2400;
+
+/// Gas paid for every storage key in the access list (EIP-2930).
+pub const TX_ACCESS_LIST_STORAGE_KEY_GAS: u64 = 1900;
+
+/// Order of the secp256k1 curve, the upper bound of the r and s values.
+const SECP256K1_N: U256 = U256([
+  0xBFD25E8CD0364141,
+  0xBAAEDCE6AF48A03B,
+  0xFFFFFFFFFFFFFFFE,
+  0xFFFFFFFFFFFFFFFF,
+]);
+
+/// Largest s value accepted since Homestead (EIP-2), n / 2.
+const SECP256K1_HALF_N: U256 = U256([
+  0xDFE92F46681B20A0,
+  0x5D576E7357A4501D,
+  0xFFFFFFFFFFFFFFFF,
+  0x7FFFFFFFFFFFFFFF,
+]);
+
+/// Computes the gas that is charged for a transaction with the given input
+/// before execution starts, excluding any access list costs.
+///
+/// Contract creations are also charged for every word of init code
+/// as specified in EIP-3860.
+pub fn intrinsic_gas(input: &[u8], is_create: bool) -> u64 {
+  let zeros = input.iter().filter(|&&b| b == 0).count() as u64;
+  let non_zeros = input.len() as u64 - zeros;
+
+  let mut gas = TX_BASE_GAS;
+  if is_create {
+    let words = (input.len() as u64 + 31) / 32;
+    gas += TX_CREATE_GAS + words * INITCODE_WORD_COST;
+  }
+  gas + zeros * TX_DATA_ZERO_GAS + non_zeros * TX_DATA_NON_ZERO_GAS
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum TransactionAction {
   Call(H160),
   Create,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// An account and storage keys that a transaction declares upfront
+/// (EIP-2930). They are paid for as part of the intrinsic gas and are
+/// warm from the start of the transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
 pub struct AccessListItem {
   pub address: H160,
   pub slots: Vec<H256>,
@@ -256,6 +311,31 @@ impl Transaction {
       Transaction::EIP1559 { value, .. } => value,
     }
   }
+
+  pub fn access_list(&self) -> &[AccessListItem] {
+    match self {
+      Transaction::Legacy { .. } => &[],
+      Transaction::EIP2930 { access_list, .. } => access_list,
+      Transaction::EIP1559 { access_list, .. } => access_list,
+    }
+  }
+
+  /// Gas charged for this transaction before any code is executed.
+  pub fn intrinsic_gas(&self) -> u64 {
+    let access_list_gas: u64 = self
+      .access_list()
+      .iter()
+      .map(|item| {
+        TX_ACCESS_LIST_ADDRESS_GAS
+          + item.slots.len() as u64 * TX_ACCESS_LIST_STORAGE_KEY_GAS
+      })
+      .sum();
+
+    intrinsic_gas(
+      &self.input(),
+      matches!(self.action(), TransactionAction::Create),
+    ) + access_list_gas
+  }
 }
 
 impl Deref for TransactionRecoveryId {
@@ -438,6 +518,42 @@ impl SignedTransaction {
   }
 }
 
+/// Decodes the v, r and s values starting at the given index and rejects
+/// signatures that no valid transaction can carry: r and s must be in
+/// [1, n), s must be in the lower half of the curve order (EIP-2) and typed
+/// transactions store the y parity, so their v must be 0 or 1.
+fn parse_signature(
+  rlp: &Rlp,
+  index: usize,
+  typed: bool,
+) -> Result<TransactionSignature, DecoderError> {
+  let v: u64 = rlp.val_at(index)?;
+  let r: U256 = rlp.val_at(index + 1)?;
+  let s: U256 = rlp.val_at(index + 2)?;
+
+  if typed && v > 1 {
+    return Err(DecoderError::Custom("invalid signature y parity"));
+  }
+  if r.is_zero() || r >= SECP256K1_N {
+    return Err(DecoderError::Custom("signature r out of range"));
+  }
+  if s.is_zero() || s > SECP256K1_HALF_N {
+    return Err(DecoderError::Custom("signature s out of range"));
+  }
+
+  let to_hash = |value: U256| {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    H256(bytes)
+  };
+
+  Ok(TransactionSignature {
+    v: TransactionRecoveryId(v),
+    r: to_hash(r),
+    s: to_hash(s),
+  })
+}
+
 /// rlp([nonce, gasPrice, gasLimit, to, value, data, init, v, r, s])
 fn parse_legacy_transaction(bytes: &[u8]) -> Result<SignedTransaction, DecoderError> {
   let rlp = Rlp::new(bytes);
@@ -446,11 +562,7 @@ fn parse_legacy_transaction(bytes: &[u8]) -> Result<SignedTransaction, DecoderEr
     return Err(DecoderError::RlpIncorrectListLen);
   }
 
-  let signature = TransactionSignature {
-    v: TransactionRecoveryId(rlp.val_at(6)?),
-    r: rlp.val_at(7)?,
-    s: rlp.val_at(8)?,
-  };
+  let signature = parse_signature(&rlp, 6, false)?;
 
   Ok(SignedTransaction {
     transaction: Transaction::Legacy {
@@ -475,11 +587,7 @@ fn parse_eip2930_transaction(bytes: &[u8]) -> Result<SignedTransaction, DecoderE
     return Err(DecoderError::RlpIncorrectListLen);
   }
 
-  let signature = TransactionSignature {
-    v: TransactionRecoveryId(rlp.val_at(8)?),
-    r: rlp.val_at(9)?,
-    s: rlp.val_at(10)?,
-  };
+  let signature = parse_signature(&rlp, 8, true)?;
 
   Ok(SignedTransaction {
     transaction: Transaction::EIP2930 {
@@ -507,11 +615,7 @@ fn parse_eip1559_transaction(bytes: &[u8]) -> Result<SignedTransaction, DecoderE
   }
 
   Ok(SignedTransaction {
-    signature: TransactionSignature {
-      v: TransactionRecoveryId(rlp.val_at(9)?),
-      r: rlp.val_at(10)?,
-      s: rlp.val_at(11)?,
-    },
+    signature: parse_signature(&rlp, 9, true)?,
     transaction: Transaction::EIP1559 {
       chain_id: rlp.val_at(0)?,
       nonce: rlp.val_at(1)?,
@@ -597,7 +701,15 @@ impl Debug for Transaction {
 mod tests {
   use {
     crate::{
-      transaction::{AccessListItem, Transaction, TransactionAction},
+      message::Message,
+      transaction::{
+        AccessListItem,
+        Transaction,
+        TransactionAction,
+        TransactionRecoveryId,
+        SECP256K1_HALF_N,
+        SECP256K1_N,
+      },
       SignedTransaction,
       H160,
       H256,
@@ -681,6 +793,35 @@ mod tests {
     }
   }
 
+  #[test]
+  fn intrinsic_gas() {
+    assert_eq!(21000, super::intrinsic_gas(&[], false));
+    assert_eq!(53000, super::intrinsic_gas(&[], true));
+    assert_eq!(21000 + 4 + 16, super::intrinsic_gas(&[0, 1], false));
+    assert_eq!(
+      53000 + 33 * 16 + 2 * 2,
+      super::intrinsic_gas(&[1; 33], true)
+    );
+
+    let transaction = Transaction::EIP2930 {
+      chain_id: 1,
+      nonce: 0,
+      gas_price: U256::zero(),
+      gas_limit: 100000,
+      action: TransactionAction::Call(H160::zero()),
+      value: U256::zero(),
+      input: vec![0xff; 4].into(),
+      access_list: vec![AccessListItem {
+        address: H160::zero(),
+        slots: vec![H256::zero(), H256::zero()],
+      }],
+    };
+    assert_eq!(
+      21000 + 4 * 16 + 2400 + 2 * 1900,
+      transaction.intrinsic_gas()
+    );
+  }
+
   #[test]
   fn decode_eip2930_transaction() {
     // https://etherscan.io/tx/0xfbf20efe99271206c0f5b497a92bee2e66f8bf9991e07648935194f17610b36e
@@ -820,4 +961,65 @@ mod tests {
       assert!(false, "decoded into wrong transaction type");
     }
   }
+
+  #[test]
+  fn message_gas_excludes_intrinsic_gas() {
+    // a plain transfer with a gas limit of exactly 21000
+    let raw = hex!(
+      "02f8720104843b9aca008504eb6480bc82520894f76c5b19e86c256
+       482f4aad1dae620a0c3ac0cd68717699d954d540080c080a05a5206a8e0486b8e101bcf
+       4ed5b290df24a4d54f1ca752c859fa19c291244b98a0177166d96fd69db70628d99855b
+       400c8a149b2254c211a0a00645830f5338218"
+    );
+
+    let transaction = SignedTransaction::try_from(&raw[..]).unwrap();
+    let message = Message::try_from(transaction).unwrap();
+    assert_eq!(0, message.gas);
+
+    let mut transaction = SignedTransaction::try_from(&raw[..]).unwrap();
+    if let Transaction::EIP1559 { gas_limit, .. } = &mut transaction.transaction {
+      *gas_limit = 20999;
+    }
+    assert!(Message::try_from(transaction).is_err());
+  }
+
+  #[test]
+  fn invalid_signatures_are_rejected() {
+    let raw = hex!(
+      "02f8720104843b9aca008504eb6480bc82520894f76c5b19e86c256
+       482f4aad1dae620a0c3ac0cd68717699d954d540080c080a05a5206a8e0486b8e101bcf
+       4ed5b290df24a4d54f1ca752c859fa19c291244b98a0177166d96fd69db70628d99855b
+       400c8a149b2254c211a0a00645830f5338218"
+    );
+
+    let to_hash = |value: U256| {
+      let mut bytes = [0u8; 32];
+      value.to_big_endian(&mut bytes);
+      H256(bytes)
+    };
+    let reencode = |change: &dyn Fn(&mut SignedTransaction)| {
+      let mut transaction = SignedTransaction::try_from(&raw[..]).unwrap();
+      change(&mut transaction);
+      SignedTransaction::try_from(&transaction.serialize()[..])
+    };
+
+    // unchanged signature still decodes after a round trip
+    assert!(reencode(&|_| {}).is_ok());
+
+    // typed transactions carry the y parity
+    assert!(reencode(&|tx| tx.signature.v = TransactionRecoveryId(27)).is_err());
+
+    // zero and out of range r
+    assert!(reencode(&|tx| tx.signature.r = H256::zero()).is_err());
+    assert!(reencode(&|tx| tx.signature.r = to_hash(SECP256K1_N)).is_err());
+
+    // high s is malleable (EIP-2)
+    assert!(reencode(&|tx| tx.signature.s = H256::zero()).is_err());
+    assert!(reencode(&|tx| tx.signature.s = to_hash(SECP256K1_HALF_N)).is_ok());
+    assert!(reencode(&|tx| {
+      let s = U256::from_big_endian(tx.signature.s.as_bytes());
+      tx.signature.s = to_hash(SECP256K1_N - s);
+    })
+    .is_err());
+  }
 }
@@ -1,5 +1,9 @@
 use {
   fixed_hash::construct_fixed_hash,
+  fvm_shared::{
+    bigint::{BigInt, Sign as BigIntSign},
+    econ::TokenAmount,
+  },
   impl_serde::{impl_fixed_hash_serde, impl_uint_serde},
   std::cmp::Ordering,
   uint::construct_uint,
@@ -109,10 +113,10 @@ pub fn u128_words_to_u256(high: u128, low: u128) -> U256 {
 
 
 #[inline]
-fn _u256_to_address(v: U256) -> H160 {
+pub fn u256_to_address(v: U256) -> H160 {
   let mut bytes = [0u8; 32];
   v.to_big_endian(&mut bytes);
-  H160::from_slice(&bytes)
+  H160::from_slice(&bytes[12..])
 }
 
 #[inline]
@@ -120,6 +124,24 @@ pub fn address_to_u256(v: H160) -> U256 {
   U256::from_big_endian(v.as_bytes())
 }
 
+/// Converts an attoFIL amount to an EVM word.
+///
+/// Returns `None` for negative amounts or amounts that do not fit in 256 bits.
+pub fn token_to_u256(amount: &TokenAmount) -> Option<U256> {
+  match amount.to_bytes_be() {
+    (BigIntSign::Minus, _) => None,
+    (_, bytes) if bytes.len() > 32 => None,
+    (_, bytes) => Some(U256::from_big_endian(&bytes)),
+  }
+}
+
+/// Converts an EVM word to an attoFIL amount.
+pub fn u256_to_token(v: U256) -> TokenAmount {
+  let mut bytes = [0u8; 32];
+  v.to_big_endian(&mut bytes);
+  BigInt::from_bytes_be(BigIntSign::Plus, &bytes)
+}
+
 const SIGN_BITMASK_U128: u128 = 0x8000_0000_0000_0000_0000_0000_0000_0000;
 const FLIPH_BITMASK_U128: u128 = 0x7FFF_FFFF_FFFF_FFFF_FFFF_FFFF_FFFF_FFFF;
 
@@ -215,7 +237,9 @@ pub fn i256_mod(mut first: U256, mut second: U256) -> U256 {
     return U256::zero();
   }
 
-  let _ = i256_sign::<true>(&mut second);
+  if i256_sign::<true>(&mut second) == Sign::Zero {
+    return U256::zero();
+  }
   let mut r = first % second;
   u256_remove_sign(&mut r);
   if r == U256::zero() {
@@ -282,4 +306,25 @@ mod tests {
     assert_eq!(i256_div(one_hundred, minus_one), neg_one_hundred);
     assert_eq!(i256_div(one_hundred, two), fifty);
   }
+
+  #[test]
+  fn mod_i256() {
+    let minus_seven = two_compl(U256::from(7));
+    let two = U256::from(2);
+
+    assert_eq!(i256_mod(minus_seven, two), two_compl(U256::from(1)));
+    assert_eq!(i256_mod(U256::from(7), two_compl(two)), U256::from(1));
+    assert_eq!(i256_mod(minus_seven, U256::zero()), U256::zero());
+    assert_eq!(i256_mod(U256::zero(), two), U256::zero());
+  }
+
+  #[test]
+  fn token_conversion() {
+    let max = u256_to_token(U256::MAX);
+    assert_eq!(token_to_u256(&max), Some(U256::MAX));
+    assert_eq!(token_to_u256(&(max + 1)), None);
+    assert_eq!(token_to_u256(&TokenAmount::from(-1)), None);
+    assert_eq!(token_to_u256(&TokenAmount::from(0)), Some(U256::zero()));
+    assert_eq!(u256_to_token(U256::from(999)), TokenAmount::from(999));
+  }
 }
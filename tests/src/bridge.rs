@@ -1,12 +1,127 @@
 use {
   crate::{sign_evm_transaction, EVMTester},
   anyhow::Result,
-  fvm_evm::{Transaction, TransactionAction},
-  fvm_ipld_encoding::RawBytes,
+  fvm_evm::{
+    id_to_h160,
+    AccessListItem,
+    AccountKind,
+    BridgeConstructorParams,
+    CallParams,
+    CallReturn,
+    CreateReturn,
+    EthereumAccount,
+    InvocationReturn,
+    Revision,
+    SignedTransaction,
+    StatusCode,
+    Transaction,
+    TransactionAction,
+    H160,
+    H256,
+    U256,
+  },
+  fvm_ipld_encoding::{from_slice, RawBytes},
+  fvm_shared::bigint::BigInt,
   libsecp256k1::SecretKey,
 };
 
 const PROCESS_TRANSACTION_METHOD_NUM: u64 = 2;
+const CALL_METHOD_NUM: u64 = 3;
+const ESTIMATE_GAS_METHOD_NUM: u64 = 4;
+const GET_ACCOUNT_METHOD_NUM: u64 = 5;
+const GET_NONCE_METHOD_NUM: u64 = 6;
+const GET_BALANCE_METHOD_NUM: u64 = 7;
+const DEPOSIT_METHOD_NUM: u64 = 8;
+const WITHDRAW_METHOD_NUM: u64 = 9;
+
+const RUNTIME_GET_CODE_SIZE_METHOD_NUM: u64 = 5;
+
+fn balance_of(tester: &mut EVMTester, address: H160) -> Result<U256> {
+  let caller = tester.accounts()[0].1;
+  Ok(from_slice(&tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    GET_BALANCE_METHOD_NUM,
+    RawBytes::serialize(address)?,
+  )?)?)
+}
+
+/// Deploys the simplecoin contract from the account of `seckey` and returns
+/// the address of the new contract.
+fn deploy_simplecoin(
+  tester: &mut EVMTester,
+  seckey: SecretKey,
+  nonce: u64,
+) -> Result<H160> {
+  let initcode = hex::decode(include_str!("../contracts/simplecoin.hex"))?;
+  deploy(tester, seckey, nonce, initcode, 0)
+}
+
+/// Runs `initcode` in a creation transaction that endows the new contract
+/// with `value`, and returns the address of the contract.
+pub(crate) fn deploy(
+  tester: &mut EVMTester,
+  seckey: SecretKey,
+  nonce: u64,
+  initcode: Vec<u8>,
+  value: u64,
+) -> Result<H160> {
+  let create_tx = Transaction::Legacy {
+    chain_id: Some(EVMTester::CHAIN_ID),
+    nonce,
+    gas_price: 150000000000u64.into(),
+    gas_limit: 500000,
+    action: TransactionAction::Create,
+    value: value.into(),
+    input: initcode.into(),
+  };
+
+  let caller = tester.accounts()[0].1;
+  let raw_tx = sign_evm_transaction(create_tx, seckey).serialize();
+  let ret: CreateReturn = from_slice(&tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    PROCESS_TRANSACTION_METHOD_NUM,
+    RawBytes::serialize(raw_tx)?,
+  )?)?;
+  match ret.is_success() {
+    true => Ok(ret.address),
+    false => Err(anyhow::anyhow!("deployment failed: {}", ret.status_code)),
+  }
+}
+
+fn simulate(tester: &mut EVMTester, params: CallParams) -> Result<CallReturn> {
+  let caller = tester.accounts()[0].1;
+  Ok(from_slice(&tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    CALL_METHOD_NUM,
+    RawBytes::serialize(params)?,
+  )?)?)
+}
+
+pub(crate) fn account_of(
+  tester: &mut EVMTester,
+  address: H160,
+) -> Result<EthereumAccount> {
+  let caller = tester.accounts()[0].1;
+  Ok(from_slice(&tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    GET_ACCOUNT_METHOD_NUM,
+    RawBytes::serialize(address)?,
+  )?)?)
+}
+
+fn nonce_of(tester: &mut EVMTester, address: H160) -> Result<u64> {
+  let caller = tester.accounts()[0].1;
+  Ok(from_slice(&tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    GET_NONCE_METHOD_NUM,
+    RawBytes::serialize(address)?,
+  )?)?)
+}
 
 #[test]
 fn deploy_contract() -> Result<()> {
@@ -17,15 +132,15 @@ fn deploy_contract() -> Result<()> {
   // create the bridge actor and instantiate it with the evm runtime code CID.
   let output = tester.construct_actor(
     EVMTester::BRIDGE_ACTOR_ADDRESS,
-    RawBytes::serialize(tester.runtime_code_cid())?,
+    RawBytes::serialize(tester.bridge_params())?,
   )?;
 
   // bridge constructor does not return anything
   assert_eq!(RawBytes::default(), output);
 
   let create_tx = Transaction::Legacy {
-    chain_id: Some(8889),
-    nonce: 1,
+    chain_id: Some(EVMTester::CHAIN_ID),
+    nonce: 0,
     gas_price: 150000000000u64.into(),
     gas_limit: 500000,
     action: TransactionAction::Create,
@@ -48,3 +163,784 @@ fn deploy_contract() -> Result<()> {
 
   Ok(())
 }
+
+#[test]
+fn query_unknown_account() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  let address = H160::repeat_byte(0xab);
+  let caller = tester.accounts()[0].1;
+
+  let account: EthereumAccount = from_slice(&tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    GET_ACCOUNT_METHOD_NUM,
+    RawBytes::serialize(address)?,
+  )?)?;
+  assert_eq!(EthereumAccount::default(), account);
+
+  let nonce: u64 = from_slice(&tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    GET_NONCE_METHOD_NUM,
+    RawBytes::serialize(address)?,
+  )?)?;
+  assert_eq!(0, nonce);
+
+  let balance: U256 = from_slice(&tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    GET_BALANCE_METHOD_NUM,
+    RawBytes::serialize(address)?,
+  )?)?;
+  assert_eq!(U256::zero(), balance);
+
+  Ok(())
+}
+
+#[test]
+fn replayed_transfer_is_rejected() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  let caller = tester.accounts()[0].1;
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let recipient = H160::repeat_byte(0xcd);
+
+  let transfer = sign_evm_transaction(
+    Transaction::Legacy {
+      chain_id: Some(EVMTester::CHAIN_ID),
+      nonce: 0,
+      gas_price: 0.into(),
+      gas_limit: 21000,
+      action: TransactionAction::Call(recipient),
+      value: 100.into(),
+      input: Vec::new().into(),
+    },
+    seckey,
+  );
+  let sender = transfer.sender_address()?;
+
+  tester.invoke_actor_with_value(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    DEPOSIT_METHOD_NUM,
+    RawBytes::serialize(sender)?,
+    BigInt::from(1000),
+  )?;
+
+  let raw_tx = RawBytes::serialize(transfer.serialize())?;
+  tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    PROCESS_TRANSACTION_METHOD_NUM,
+    raw_tx.clone(),
+  )?;
+
+  // the same signed bytes must not move the value a second time
+  assert!(tester
+    .invoke_actor(
+      caller,
+      EVMTester::BRIDGE_ACTOR_ADDRESS,
+      PROCESS_TRANSACTION_METHOD_NUM,
+      raw_tx,
+    )
+    .is_err());
+
+  assert_eq!(U256::from(900), balance_of(&mut tester, sender)?);
+  assert_eq!(U256::from(100), balance_of(&mut tester, recipient)?);
+  assert_eq!(1, nonce_of(&mut tester, sender)?);
+
+  Ok(())
+}
+
+#[test]
+fn transfer_for_another_chain_is_rejected() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  let transfer = sign_evm_transaction(
+    Transaction::Legacy {
+      chain_id: Some(EVMTester::CHAIN_ID + 1),
+      nonce: 0,
+      gas_price: 0.into(),
+      gas_limit: 21000,
+      action: TransactionAction::Call(H160::repeat_byte(0xcd)),
+      value: 0.into(),
+      input: Vec::new().into(),
+    },
+    SecretKey::random(&mut rand::thread_rng()),
+  );
+
+  assert!(tester
+    .invoke_actor(
+      tester.accounts()[0].1,
+      EVMTester::BRIDGE_ACTOR_ADDRESS,
+      PROCESS_TRANSACTION_METHOD_NUM,
+      RawBytes::serialize(transfer.serialize())?,
+    )
+    .is_err());
+
+  Ok(())
+}
+
+#[test]
+fn deposit_and_withdraw() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  let caller = tester.accounts()[0].1;
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let bridge = id_to_h160(EVMTester::BRIDGE_ACTOR_ADDRESS.id()?);
+
+  let withdrawal = |nonce: u64, amount: u64| {
+    sign_evm_transaction(
+      Transaction::Legacy {
+        chain_id: Some(EVMTester::CHAIN_ID),
+        nonce,
+        gas_price: 0.into(),
+        gas_limit: 21000,
+        action: TransactionAction::Call(bridge),
+        value: amount.into(),
+        input: caller.to_bytes().into(),
+      },
+      seckey,
+    )
+  };
+  let sender = withdrawal(0, 0).sender_address()?;
+
+  tester.invoke_actor_with_value(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    DEPOSIT_METHOD_NUM,
+    RawBytes::serialize(sender)?,
+    BigInt::from(1000),
+  )?;
+  assert_eq!(U256::from(1000), balance_of(&mut tester, sender)?);
+
+  let withdraw_params =
+    |tx: Vec<u8>, amount: u64| RawBytes::serialize((U256::from(amount), caller, tx));
+
+  let signed = withdrawal(0, 400).serialize();
+  tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    WITHDRAW_METHOD_NUM,
+    withdraw_params(signed.clone(), 400)?,
+  )?;
+  assert_eq!(U256::from(600), balance_of(&mut tester, sender)?);
+  assert_eq!(1, nonce_of(&mut tester, sender)?);
+
+  // a signed withdrawal is usable only once
+  assert!(tester
+    .invoke_actor(
+      caller,
+      EVMTester::BRIDGE_ACTOR_ADDRESS,
+      WITHDRAW_METHOD_NUM,
+      withdraw_params(signed, 400)?,
+    )
+    .is_err());
+
+  // and it is never a valid transaction on its own, even with a fresh nonce
+  assert!(tester
+    .invoke_actor(
+      caller,
+      EVMTester::BRIDGE_ACTOR_ADDRESS,
+      PROCESS_TRANSACTION_METHOD_NUM,
+      RawBytes::serialize(withdrawal(1, 400).serialize())?,
+    )
+    .is_err());
+
+  assert_eq!(U256::from(600), balance_of(&mut tester, sender)?);
+  assert_eq!(1, nonce_of(&mut tester, sender)?);
+
+  Ok(())
+}
+
+#[test]
+fn deploy_twice_from_same_sender() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let first = deploy_simplecoin(&mut tester, seckey, 0)?;
+  let second = deploy_simplecoin(&mut tester, seckey, 1)?;
+
+  // every deployment consumes a nonce, so each gets its own address
+  assert_ne!(first, second);
+
+  let caller = tester.accounts()[0].1;
+  for contract in [first, second] {
+    let account: EthereumAccount = from_slice(&tester.invoke_actor(
+      caller,
+      EVMTester::BRIDGE_ACTOR_ADDRESS,
+      GET_ACCOUNT_METHOD_NUM,
+      RawBytes::serialize(contract)?,
+    )?)?;
+    assert!(matches!(account.kind, AccountKind::Contract { .. }));
+  }
+
+  Ok(())
+}
+
+#[test]
+fn failed_create_consumes_nonce() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  let caller = tester.accounts()[0].1;
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let create = |nonce: u64, initcode: &str| -> Result<SignedTransaction> {
+    Ok(sign_evm_transaction(
+      Transaction::Legacy {
+        chain_id: Some(EVMTester::CHAIN_ID),
+        nonce,
+        gas_price: 0.into(),
+        gas_limit: 100000,
+        action: TransactionAction::Create,
+        value: 400.into(),
+        input: hex::decode(initcode)?.into(),
+      },
+      seckey,
+    ))
+  };
+  let sender = create(0, "")?.sender_address()?;
+
+  tester.invoke_actor_with_value(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    DEPOSIT_METHOD_NUM,
+    RawBytes::serialize(sender)?,
+    BigInt::from(1000),
+  )?;
+
+  // PUSH1 0 PUSH1 0 REVERT
+  let ret: CreateReturn = from_slice(&tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    PROCESS_TRANSACTION_METHOD_NUM,
+    RawBytes::serialize(create(0, "60006000fd")?.serialize())?,
+  )?)?;
+  assert_eq!(StatusCode::Revert, ret.status_code);
+  assert!(ret.gas_used > 21000);
+
+  // the endowment stays with the sender, the nonce is consumed
+  assert_eq!(U256::from(1000), balance_of(&mut tester, sender)?);
+  assert_eq!(U256::zero(), balance_of(&mut tester, ret.address)?);
+  assert_eq!(1, nonce_of(&mut tester, sender)?);
+
+  // so the same creation cannot be replayed
+  assert!(tester
+    .invoke_actor(
+      caller,
+      EVMTester::BRIDGE_ACTOR_ADDRESS,
+      PROCESS_TRANSACTION_METHOD_NUM,
+      RawBytes::serialize(create(0, "60006000fd")?.serialize())?,
+    )
+    .is_err());
+
+  Ok(())
+}
+
+#[test]
+fn selfdestruct_deletes_contract() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+
+  // before Cancun SELFDESTRUCT deletes any contract (EIP-6780)
+  let params = BridgeConstructorParams {
+    revision: Revision::London,
+    ..tester.bridge_params()
+  };
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(params)?,
+  )?;
+
+  let caller = tester.accounts()[0].1;
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let sender = sign_evm_transaction(
+    Transaction::Legacy {
+      chain_id: Some(EVMTester::CHAIN_ID),
+      nonce: 0,
+      gas_price: 0.into(),
+      gas_limit: 21000,
+      action: TransactionAction::Create,
+      value: 0.into(),
+      input: Vec::new().into(),
+    },
+    seckey,
+  )
+  .sender_address()?;
+
+  tester.invoke_actor_with_value(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    DEPOSIT_METHOD_NUM,
+    RawBytes::serialize(sender)?,
+    BigInt::from(1000),
+  )?;
+
+  // runtime code CALLER SELFDESTRUCT, returned by the constructor
+  let initcode = hex::decode("6133ff6000526002601ef3")?;
+  let contract = deploy(&mut tester, seckey, 0, initcode, 400)?;
+  assert_eq!(U256::from(600), balance_of(&mut tester, sender)?);
+  assert_eq!(U256::from(400), balance_of(&mut tester, contract)?);
+
+  let fil_account = match account_of(&mut tester, contract)?.kind {
+    AccountKind::Contract { fil_account } => fil_account,
+    kind => panic!("expected a contract account, got {kind:?}"),
+  };
+
+  let destroy = sign_evm_transaction(
+    Transaction::Legacy {
+      chain_id: Some(EVMTester::CHAIN_ID),
+      nonce: 1,
+      gas_price: 0.into(),
+      gas_limit: 100000,
+      action: TransactionAction::Call(contract),
+      value: 0.into(),
+      input: Vec::new().into(),
+    },
+    seckey,
+  );
+  tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    PROCESS_TRANSACTION_METHOD_NUM,
+    RawBytes::serialize(destroy.serialize())?,
+  )?;
+
+  // the balance went back to the caller, then the account and its actor
+  // were removed
+  assert_eq!(U256::from(1000), balance_of(&mut tester, sender)?);
+  assert_eq!(
+    EthereumAccount::default(),
+    account_of(&mut tester, contract)?
+  );
+  assert!(tester
+    .invoke_actor(
+      caller,
+      fil_account,
+      RUNTIME_GET_CODE_SIZE_METHOD_NUM,
+      RawBytes::default()
+    )
+    .is_err());
+
+  Ok(())
+}
+
+#[test]
+fn selfdestruct_in_constructor() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  // CALLER SELFDESTRUCT, the contract never gets any code
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let contract = deploy(&mut tester, seckey, 0, hex::decode("33ff")?, 0)?;
+
+  assert_eq!(
+    EthereumAccount::default(),
+    account_of(&mut tester, contract)?
+  );
+
+  Ok(())
+}
+
+#[test]
+fn selfdestruct_to_native_actor() -> Result<()> {
+  let mut tester = EVMTester::new::<2>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  // the second account never sends a message, so its balance only
+  // changes by what the contract leaves to it
+  let caller = tester.accounts()[0].1;
+  let (beneficiary_id, beneficiary) = tester.accounts()[1];
+  let fil_before = tester.fil_balance(beneficiary)?;
+
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let sender = sign_evm_transaction(
+    Transaction::Legacy {
+      chain_id: Some(EVMTester::CHAIN_ID),
+      nonce: 0,
+      gas_price: 0.into(),
+      gas_limit: 21000,
+      action: TransactionAction::Create,
+      value: 0.into(),
+      input: Vec::new().into(),
+    },
+    seckey,
+  )
+  .sender_address()?;
+  tester.invoke_actor_with_value(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    DEPOSIT_METHOD_NUM,
+    RawBytes::serialize(sender)?,
+    BigInt::from(1000),
+  )?;
+
+  // runtime code PUSH20 <beneficiary> SELFDESTRUCT, copied out of the
+  // initcode
+  let runtime = format!("73{}ff", hex::encode(id_to_h160(beneficiary_id)));
+  let initcode = hex::decode(format!("601680600b6000396000f3{runtime}"))?;
+  let contract = deploy(&mut tester, seckey, 0, initcode, 400)?;
+
+  let destroy = sign_evm_transaction(
+    Transaction::Legacy {
+      chain_id: Some(EVMTester::CHAIN_ID),
+      nonce: 1,
+      gas_price: 0.into(),
+      gas_limit: 100000,
+      action: TransactionAction::Call(contract),
+      value: 0.into(),
+      input: Vec::new().into(),
+    },
+    seckey,
+  );
+  let ret: InvocationReturn = from_slice(&tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    PROCESS_TRANSACTION_METHOD_NUM,
+    RawBytes::serialize(destroy.serialize())?,
+  )?)?;
+  assert!(ret.result.is_success(), "{}", ret.result.error_message());
+
+  // the balance left the EVM world as FIL
+  assert_eq!(U256::zero(), balance_of(&mut tester, contract)?);
+  assert_eq!(
+    fil_before + BigInt::from(400),
+    tester.fil_balance(beneficiary)?
+  );
+
+  Ok(())
+}
+
+#[test]
+fn simulate_call_that_writes_storage() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  // runtime code PUSH1 0x2a PUSH1 0 SSTORE STOP
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let initcode = hex::decode("65602a600055006000526006601af3")?;
+  let contract = deploy(&mut tester, seckey, 0, initcode, 0)?;
+
+  let ret = simulate(&mut tester, CallParams {
+    from: H160::repeat_byte(0xaa),
+    to: Some(contract),
+    data: Default::default(),
+    value: U256::zero(),
+    gas: 100000,
+  })?;
+  assert!(ret.is_success(), "{}", ret.error_message());
+
+  // and the simulated write was not persisted
+  let ret = simulate(&mut tester, CallParams {
+    from: H160::repeat_byte(0xaa),
+    to: Some(contract),
+    data: Default::default(),
+    value: U256::zero(),
+    gas: 100000,
+  })?;
+  assert_eq!(ret.gas_used, 3 + 3 + 2100 + 20000);
+
+  Ok(())
+}
+
+#[test]
+fn simulate_create_runs_constructor() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  // the simplecoin constructor writes the balance of the origin
+  let ret = simulate(&mut tester, CallParams {
+    from: H160::repeat_byte(0xaa),
+    to: None,
+    data: hex::decode(include_str!("../contracts/simplecoin.hex"))?.into(),
+    value: U256::zero(),
+    gas: 1000000,
+  })?;
+  assert!(ret.is_success(), "{}", ret.error_message());
+  assert!(!ret.output_data.is_empty());
+
+  Ok(())
+}
+
+#[test]
+fn simulate_create_runs_at_contract_address() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let sender = sign_evm_transaction(
+    Transaction::Legacy {
+      chain_id: Some(EVMTester::CHAIN_ID),
+      nonce: 0,
+      gas_price: 0.into(),
+      gas_limit: 21000,
+      action: TransactionAction::Create,
+      value: 0.into(),
+      input: Vec::new().into(),
+    },
+    seckey,
+  )
+  .sender_address()?;
+
+  // ADDRESS PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+  let initcode = hex::decode("3060005260206000f3")?;
+  let ret = simulate(&mut tester, CallParams {
+    from: sender,
+    to: None,
+    data: initcode.clone().into(),
+    value: U256::zero(),
+    gas: 100000,
+  })?;
+  assert!(ret.is_success(), "{}", ret.error_message());
+
+  // the simulated constructor observes the address it is deployed at
+  let contract = deploy(&mut tester, seckey, 0, initcode, 0)?;
+  assert_eq!(contract, H160::from_slice(&ret.output_data[12..]));
+
+  Ok(())
+}
+
+#[test]
+fn estimate_gas_finds_smallest_limit() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  // runtime code PUSH1 0x2a PUSH1 0 SSTORE STOP
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let initcode = hex::decode("65602a600055006000526006601af3")?;
+  let contract = deploy(&mut tester, seckey, 0, initcode, 0)?;
+
+  let caller = tester.accounts()[0].1;
+  let gas: u64 = from_slice(&tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    ESTIMATE_GAS_METHOD_NUM,
+    RawBytes::serialize(CallParams {
+      from: H160::repeat_byte(0xaa),
+      to: Some(contract),
+      data: Default::default(),
+      value: U256::zero(),
+      gas: 0,
+    })?,
+  )?)?;
+
+  // the search lands on the exact cost of the cold write from zero,
+  // below the optimistic guess of 64/63 of the gas used
+  assert_eq!(gas, 21000 + 3 + 3 + 2100 + 20000);
+
+  // an allowance that does not even cover the intrinsic gas is rejected
+  // rather than replaced by the default cap
+  assert!(tester
+    .invoke_actor(
+      caller,
+      EVMTester::BRIDGE_ACTOR_ADDRESS,
+      ESTIMATE_GAS_METHOD_NUM,
+      RawBytes::serialize(CallParams {
+        from: H160::repeat_byte(0xaa),
+        to: Some(contract),
+        data: Default::default(),
+        value: U256::zero(),
+        gas: 20000,
+      })?,
+    )
+    .is_err());
+
+  Ok(())
+}
+
+#[test]
+fn reverted_call_returns_reason() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  let caller = tester.accounts()[0].1;
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let call = |nonce: u64, to: H160, value: u64| {
+    sign_evm_transaction(
+      Transaction::Legacy {
+        chain_id: Some(EVMTester::CHAIN_ID),
+        nonce,
+        gas_price: 0.into(),
+        gas_limit: 100000,
+        action: TransactionAction::Call(to),
+        value: value.into(),
+        input: Vec::new().into(),
+      },
+      seckey,
+    )
+  };
+  let sender = call(0, H160::zero(), 0).sender_address()?;
+
+  tester.invoke_actor_with_value(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    DEPOSIT_METHOD_NUM,
+    RawBytes::serialize(sender)?,
+    BigInt::from(1000),
+  )?;
+
+  // runtime code that reverts with Error("no"), copied out of the initcode
+  let revert = "6308c379a060e01b60005260206004526002602452616e6f60f01b60445260646000fd";
+  let initcode = hex::decode(format!("602380600b6000396000f3{revert}"))?;
+  let contract = deploy(&mut tester, seckey, 0, initcode, 0)?;
+
+  let ret: InvocationReturn = from_slice(&tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    PROCESS_TRANSACTION_METHOD_NUM,
+    RawBytes::serialize(call(1, contract, 100).serialize())?,
+  )?)?;
+
+  assert_eq!(StatusCode::Revert, ret.result.status_code);
+  assert_eq!(Some("no".to_string()), ret.revert_reason);
+  assert_eq!(100, ret.result.revert_data.len());
+  assert!(ret.result.output_data.is_empty());
+
+  // the value stays with the sender, the nonce is consumed
+  assert_eq!(U256::from(1000), balance_of(&mut tester, sender)?);
+  assert_eq!(U256::zero(), balance_of(&mut tester, contract)?);
+  assert_eq!(2, nonce_of(&mut tester, sender)?);
+
+  Ok(())
+}
+
+#[test]
+fn call_returns_logs() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  // runtime code PUSH1 0x2a PUSH1 0 MSTORE PUSH1 0xbb PUSH1 32 PUSH1 0 LOG1
+  // STOP, copied out of the initcode
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let initcode = hex::decode("600d80600b6000396000f3602a60005260bb60206000a100")?;
+  let contract = deploy(&mut tester, seckey, 0, initcode, 0)?;
+
+  let call = sign_evm_transaction(
+    Transaction::Legacy {
+      chain_id: Some(EVMTester::CHAIN_ID),
+      nonce: 1,
+      gas_price: 0.into(),
+      gas_limit: 100000,
+      action: TransactionAction::Call(contract),
+      value: 0.into(),
+      input: Vec::new().into(),
+    },
+    seckey,
+  );
+  let ret: InvocationReturn = from_slice(&tester.invoke_actor(
+    tester.accounts()[0].1,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    PROCESS_TRANSACTION_METHOD_NUM,
+    RawBytes::serialize(call.serialize())?,
+  )?)?;
+
+  assert!(ret.result.is_success(), "{}", ret.result.error_message());
+  assert_eq!(1, ret.logs.len());
+  assert_eq!(contract, ret.logs[0].address);
+  assert_eq!(vec![U256::from(0xbb)], ret.logs[0].topics);
+  assert_eq!(U256::from(0x2a), U256::from_big_endian(&ret.logs[0].data));
+
+  Ok(())
+}
+
+#[test]
+fn access_list_warms_storage_keys() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  // runtime code PUSH1 0 SLOAD STOP, copied out of the initcode
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let initcode = hex::decode("600480600b6000396000f360005400")?;
+  let contract = deploy(&mut tester, seckey, 0, initcode, 0)?;
+
+  let mut process = |tx: Transaction| -> Result<InvocationReturn> {
+    let caller = tester.accounts()[0].1;
+    Ok(from_slice(&tester.invoke_actor(
+      caller,
+      EVMTester::BRIDGE_ACTOR_ADDRESS,
+      PROCESS_TRANSACTION_METHOD_NUM,
+      RawBytes::serialize(sign_evm_transaction(tx, seckey).serialize())?,
+    )?)?)
+  };
+
+  let cold = process(Transaction::Legacy {
+    chain_id: Some(EVMTester::CHAIN_ID),
+    nonce: 1,
+    gas_price: 0.into(),
+    gas_limit: 100000,
+    action: TransactionAction::Call(contract),
+    value: 0.into(),
+    input: Vec::new().into(),
+  })?;
+  assert!(cold.result.is_success(), "{}", cold.result.error_message());
+  assert_eq!(21000 + 3 + 2100, cold.result.gas_used);
+
+  // the listed key is paid for up front and read at the warm price
+  let warm = process(Transaction::EIP2930 {
+    chain_id: EVMTester::CHAIN_ID,
+    nonce: 2,
+    gas_price: 0.into(),
+    gas_limit: 100000,
+    action: TransactionAction::Call(contract),
+    value: 0.into(),
+    input: Vec::new().into(),
+    access_list: vec![AccessListItem {
+      address: contract,
+      slots: vec![H256::zero()],
+    }],
+  })?;
+  assert!(warm.result.is_success(), "{}", warm.result.error_message());
+  assert_eq!(21000 + 2400 + 1900 + 3 + 100, warm.result.gas_used);
+
+  Ok(())
+}
@@ -1,8 +1,13 @@
 use {
   anyhow::Result,
   cid::Cid,
-  fvm::executor::{ApplyKind, Executor},
+  fvm::{
+    executor::{ApplyKind, Executor},
+    machine::Machine,
+  },
   fvm_evm::{
+    BridgeConstructorParams,
+    Revision,
     SignedTransaction,
     Transaction,
     TransactionRecoveryId,
@@ -15,6 +20,7 @@ use {
   fvm_shared::{
     address::Address,
     bigint::{BigInt, Zero},
+    econ::TokenAmount,
     message::Message,
     state::StateTreeVersion,
     version::NetworkVersion,
@@ -35,6 +41,10 @@ mod bridge;
 #[cfg(test)]
 mod runtime;
 
+pub mod statetest;
+mod trie;
+pub mod txtest;
+
 pub struct EVMTester {
   _bridge_code_cid: Cid,
   runtime_code_cid: Cid,
@@ -47,6 +57,7 @@ pub struct EVMTester {
 impl EVMTester {
   pub const BRIDGE_ACTOR_ADDRESS: Address = Address::new_id(10002);
   pub const BRIDGE_ACTOR_WASM_PATH: &'static str = "../wasm/fvm_evm_bridge.compact.wasm";
+  pub const CHAIN_ID: u64 = 8889;
   pub const INIT_ACTOR_ADDRESS: Address = Address::new_id(1);
   pub const RUNTIME_ACTOR_ADDRESS: Address = Address::new_id(10001);
   pub const RUNTIME_ACTOR_WASM_PATH: &'static str =
@@ -105,10 +116,33 @@ impl EVMTester {
     &self.runtime_code_cid
   }
 
+  /// Constructor parameters of a bridge running the latest revision.
+  pub fn bridge_params(&self) -> BridgeConstructorParams {
+    BridgeConstructorParams {
+      runtime_cid: self.runtime_code_cid,
+      revision: Revision::LATEST,
+      chain_id: Self::CHAIN_ID,
+    }
+  }
+
   pub fn accounts(&self) -> &[Account] {
     &self.accounts
   }
 
+  /// FIL balance of an actor, zero if the actor does not exist.
+  pub fn fil_balance(&self, address: Address) -> Result<TokenAmount> {
+    match self.instance.executor {
+      Some(ref executor) => Ok(
+        executor
+          .state_tree()
+          .get_actor(&address)?
+          .map(|actor| actor.balance)
+          .unwrap_or_default(),
+      ),
+      None => Err(anyhow::anyhow!("executor not initialized")),
+    }
+  }
+
   pub fn send_message(
     &mut self,
     from: Address,
@@ -116,6 +150,18 @@ impl EVMTester {
     method_num: MethodNum,
     params: RawBytes,
     kind: ApplyKind,
+  ) -> Result<RawBytes> {
+    self.send_message_with_value(from, to, method_num, params, TokenAmount::zero(), kind)
+  }
+
+  pub fn send_message_with_value(
+    &mut self,
+    from: Address,
+    to: Address,
+    method_num: MethodNum,
+    params: RawBytes,
+    value: TokenAmount,
+    kind: ApplyKind,
   ) -> Result<RawBytes> {
     let sequence = match self.sequences.entry(from) {
       Entry::Occupied(mut o) => {
@@ -130,6 +176,7 @@ impl EVMTester {
       gas_limit: 10000000000,
       method_num,
       params,
+      value,
       sequence,
       ..Message::default()
     };
@@ -196,6 +243,25 @@ impl EVMTester {
   ) -> Result<RawBytes> {
     self.send_explicit_message(caller, address, method, params)
   }
+
+  /// Invokes an actor method with FIL attached to the message.
+  pub fn invoke_actor_with_value(
+    &mut self,
+    caller: Address,
+    address: Address,
+    method: MethodNum,
+    params: RawBytes,
+    value: TokenAmount,
+  ) -> Result<RawBytes> {
+    self.send_message_with_value(
+      caller,
+      address,
+      method,
+      params,
+      value,
+      ApplyKind::Explicit,
+    )
+  }
 }
 
 pub fn sign_evm_transaction(
@@ -1,21 +1,140 @@
 use {
-  crate::EVMTester,
+  crate::{
+    bridge::{account_of, deploy},
+    sign_evm_transaction,
+    EVMTester,
+  },
   anyhow::Result,
-  fvm_evm::{EthereumAccount, H160, U256},
+  fvm_evm::{
+    AccountKind,
+    EthereumAccount,
+    Transaction,
+    TransactionAction,
+    H160,
+    H256,
+    U256,
+  },
   fvm_ipld_encoding::{from_slice, to_vec, RawBytes},
+  libsecp256k1::SecretKey,
+  sha3::{Digest, Keccak256},
 };
 
+const BRIDGE_PROCESS_TRANSACTION_METHOD_NUM: u64 = 2;
+const GET_STORAGE_VALUE_METHOD_NUM: u64 = 3;
+const GET_CODE_HASH_METHOD_NUM: u64 = 4;
+const GET_CODE_SIZE_METHOD_NUM: u64 = 5;
+
+#[test]
+fn query_contract_state() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  // runtime code PUSH1 0x2a PUSH1 0 SSTORE STOP
+  let code = hex::decode("602a60005500")?;
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let initcode = hex::decode("65602a600055006000526006601af3")?;
+  let contract = deploy(&mut tester, seckey, 0, initcode, 0)?;
+  let fil_account = match account_of(&mut tester, contract)?.kind {
+    AccountKind::Contract { fil_account } => fil_account,
+    kind => panic!("expected a contract account, got {kind:?}"),
+  };
+
+  // run the contract once so that it writes its storage
+  let store = sign_evm_transaction(
+    Transaction::Legacy {
+      chain_id: Some(EVMTester::CHAIN_ID),
+      nonce: 1,
+      gas_price: 0.into(),
+      gas_limit: 100000,
+      action: TransactionAction::Call(contract),
+      value: 0.into(),
+      input: Vec::new().into(),
+    },
+    seckey,
+  );
+  let caller = tester.accounts()[0].1;
+  tester.invoke_actor(
+    caller,
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    BRIDGE_PROCESS_TRANSACTION_METHOD_NUM,
+    RawBytes::serialize(store.serialize())?,
+  )?;
+
+  let storage_value = |tester: &mut EVMTester, key: u64| -> Result<U256> {
+    Ok(from_slice(&tester.invoke_actor(
+      caller,
+      fil_account,
+      GET_STORAGE_VALUE_METHOD_NUM,
+      RawBytes::serialize(U256::from(key))?,
+    )?)?)
+  };
+  assert_eq!(U256::from(0x2a), storage_value(&mut tester, 0)?);
+  // keys that were never written read as zero
+  assert_eq!(U256::zero(), storage_value(&mut tester, 1)?);
+
+  let code_hash: H256 = from_slice(&tester.invoke_actor(
+    caller,
+    fil_account,
+    GET_CODE_HASH_METHOD_NUM,
+    RawBytes::default(),
+  )?)?;
+  assert_eq!(H256::from_slice(&Keccak256::digest(&code)), code_hash);
+
+  let code_size: u64 = from_slice(&tester.invoke_actor(
+    caller,
+    fil_account,
+    GET_CODE_SIZE_METHOD_NUM,
+    RawBytes::default(),
+  )?)?;
+  assert_eq!(code.len() as u64, code_size);
+
+  Ok(())
+}
+
+#[test]
+fn constructor_writes_own_storage() -> Result<()> {
+  let mut tester = EVMTester::new::<1>()?;
+  tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
+
+  // ADDRESS PUSH1 0 SSTORE, then return the runtime code STOP
+  let seckey = SecretKey::random(&mut rand::thread_rng());
+  let initcode = hex::decode("30600055600060005360016000f3")?;
+  let contract = deploy(&mut tester, seckey, 0, initcode, 0)?;
+  let fil_account = match account_of(&mut tester, contract)?.kind {
+    AccountKind::Contract { fil_account } => fil_account,
+    kind => panic!("expected a contract account, got {kind:?}"),
+  };
+
+  let stored: U256 = from_slice(&tester.invoke_actor(
+    tester.accounts()[0].1,
+    fil_account,
+    GET_STORAGE_VALUE_METHOD_NUM,
+    RawBytes::serialize(U256::zero())?,
+  )?)?;
+  assert_eq!(U256::from_big_endian(contract.as_bytes()), stored);
+
+  Ok(())
+}
+
 #[test]
 #[ignore]
 fn cross_contract_smoke() -> Result<()> {
-  const RETREIVE_METHOD_NUM: u64 = 2;
+  const RETREIVE_METHOD_NUM: u64 = 5;
   const CONSTRUCT_ZERO_ACCOUNT_NUM: u64 = 2;
 
   let mut tester = EVMTester::new::<1>()?;
 
   // construct registry
-  let output =
-    tester.construct_actor(EVMTester::BRIDGE_ACTOR_ADDRESS, RawBytes::default())?;
+  let output = tester.construct_actor(
+    EVMTester::BRIDGE_ACTOR_ADDRESS,
+    RawBytes::serialize(tester.bridge_params())?,
+  )?;
 
   // registry constructor does not return anything
   assert_eq!(RawBytes::default(), output);
@@ -0,0 +1,635 @@
+//! Runner for Ethereum GeneralStateTests fixtures.
+//!
+//! Every fixture describes a pre-state, a block environment and a set of
+//! transactions. Each transaction is executed with the `fvm-evm`
+//! interpreter on top of an [`InMemoryHost`], and the resulting state root
+//! and logs hash are compared with the ones recorded for every fork.
+//!
+//! The fixtures are in the filled JSON format of
+//! <https://github.com/ethereum/tests>. A small set is vendored under
+//! `fixtures/GeneralStateTests`; set `STATE_TESTS_DIR` to run a full
+//! checkout instead.
+//!
+//! Every case has to pass, except for the ones that need a feature listed
+//! in [`UNSUPPORTED`], which are reported as skipped.
+
+use {
+  crate::trie::trie_root,
+  anyhow::{anyhow, Context, Result},
+  bytes::Bytes,
+  fvm_evm::{
+    intrinsic_gas,
+    testing::{InMemoryAccount, InMemoryHost},
+    Call,
+    CallKind,
+    Host,
+    Log,
+    Message,
+    Revision,
+    TransactionContext,
+    H160,
+    H256,
+    U256,
+  },
+  libsecp256k1::{PublicKey, SecretKey},
+  rlp::RlpStream,
+  serde::{de::DeserializeOwned, Deserialize, Deserializer},
+  sha3::{Digest, Keccak256},
+  std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display},
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+  },
+};
+
+/// Forks in activation order, used to pick fork dependent behavior.
+const FORKS: &[&str] = &[
+  "Frontier",
+  "Homestead",
+  "EIP150",
+  "EIP158",
+  "Byzantium",
+  "Constantinople",
+  "ConstantinopleFix",
+  "Istanbul",
+  "Berlin",
+  "London",
+  "Merge",
+  "Paris",
+  "Shanghai",
+  "Cancun",
+];
+
+/// Chain id used by all state test transactions.
+const CHAIN_ID: u64 = 1;
+
+/// Features the runner does not support, along with the check that tells
+/// whether a test needs them.
+pub const UNSUPPORTED: &[(&str, fn(&StateTest) -> bool)] =
+  &[("contract creation", is_creation)];
+
+fn is_creation(test: &StateTest) -> bool {
+  test.transaction.to.trim_start_matches("0x").is_empty()
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateTest {
+  pub env: Env,
+  pub pre: BTreeMap<H160, AccountState>,
+  pub transaction: TransactionParts,
+  pub post: BTreeMap<String, Vec<PostState>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Env {
+  pub current_coinbase: H160,
+  #[serde(default)]
+  pub current_difficulty: U256,
+  pub current_gas_limit: U256,
+  pub current_number: U256,
+  pub current_timestamp: U256,
+  #[serde(default)]
+  pub current_base_fee: Option<U256>,
+  #[serde(default)]
+  pub current_random: Option<H256>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccountState {
+  pub balance: U256,
+  #[serde(deserialize_with = "hex_bytes")]
+  pub code: Bytes,
+  pub nonce: U256,
+  pub storage: BTreeMap<U256, U256>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionParts {
+  #[serde(deserialize_with = "hex_bytes_list")]
+  pub data: Vec<Bytes>,
+  pub gas_limit: Vec<U256>,
+  #[serde(default)]
+  pub gas_price: Option<U256>,
+  #[serde(default)]
+  pub max_fee_per_gas: Option<U256>,
+  #[serde(default)]
+  pub max_priority_fee_per_gas: Option<U256>,
+  pub nonce: U256,
+  pub secret_key: H256,
+  #[serde(default)]
+  pub sender: Option<H160>,
+  /// Empty for contract creations.
+  pub to: String,
+  pub value: Vec<U256>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostState {
+  pub hash: H256,
+  pub logs: H256,
+  pub indexes: Indexes,
+  #[serde(default)]
+  pub expect_exception: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Indexes {
+  pub data: usize,
+  pub gas: usize,
+  pub value: usize,
+}
+
+pub(crate) fn hex_bytes<'de, D: Deserializer<'de>>(
+  deserializer: D,
+) -> Result<Bytes, D::Error> {
+  let s = String::deserialize(deserializer)?;
+  hex::decode(s.trim_start_matches("0x"))
+    .map(Bytes::from)
+    .map_err(serde::de::Error::custom)
+}
+
+fn hex_bytes_list<'de, D: Deserializer<'de>>(
+  deserializer: D,
+) -> Result<Vec<Bytes>, D::Error> {
+  Vec::<String>::deserialize(deserializer)?
+    .iter()
+    .map(|s| hex::decode(s.trim_start_matches("0x")).map(Bytes::from))
+    .collect::<Result<_, _>>()
+    .map_err(serde::de::Error::custom)
+}
+
+/// Loads all fixtures from JSON files in `dir` and its subdirectories.
+///
+/// Tests are returned sorted by name, which is the name of the file
+/// followed by the name of the test within the file.
+pub fn load_tests<T: DeserializeOwned>(dir: &Path) -> Result<Vec<(String, T)>> {
+  let mut tests = vec![];
+  for entry in fs::read_dir(dir).with_context(|| format!("reading {dir:?}"))? {
+    let path = entry?.path();
+    if path.is_dir() {
+      tests.extend(load_tests(&path)?);
+    } else if path.extension().map_or(false, |ext| ext == "json") {
+      let file: BTreeMap<String, T> = serde_json::from_slice(&fs::read(&path)?)
+        .with_context(|| format!("parsing {path:?}"))?;
+      let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+      tests.extend(
+        file
+          .into_iter()
+          .map(|(name, test)| (format!("{stem}/{name}"), test)),
+      );
+    }
+  }
+  tests.sort_by(|a, b| a.0.cmp(&b.0));
+  Ok(tests)
+}
+
+/// Outcome of running fixtures, grouped by fork.
+#[derive(Debug, Default)]
+pub struct Report {
+  pub forks: BTreeMap<String, ForkReport>,
+}
+
+#[derive(Debug, Default)]
+pub struct ForkReport {
+  pub passed: usize,
+  pub failures: Vec<Failure>,
+  /// Cases that were not run, the reason being the unsupported feature.
+  pub skipped: Vec<Failure>,
+}
+
+#[derive(Debug)]
+pub struct Failure {
+  /// Name of the test and the index of the post-state within the fork.
+  pub case: String,
+  pub reason: String,
+}
+
+impl Report {
+  pub fn passed(&self) -> usize {
+    self.forks.values().map(|f| f.passed).sum()
+  }
+
+  pub fn failed(&self) -> usize {
+    self.forks.values().map(|f| f.failures.len()).sum()
+  }
+
+  pub fn skipped(&self) -> usize {
+    self.forks.values().map(|f| f.skipped.len()).sum()
+  }
+}
+
+impl Display for Report {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (fork, report) in &self.forks {
+      writeln!(
+        f,
+        "{fork}: {} passed, {} failed, {} skipped",
+        report.passed,
+        report.failures.len(),
+        report.skipped.len()
+      )?;
+      for failure in &report.failures {
+        writeln!(f, "  {}: {}", failure.case, failure.reason)?;
+      }
+      for skipped in &report.skipped {
+        writeln!(f, "  {}: skipped, {}", skipped.case, skipped.reason)?;
+      }
+    }
+    write!(
+      f,
+      "total: {} passed, {} failed, {} skipped",
+      self.passed(),
+      self.failed(),
+      self.skipped()
+    )
+  }
+}
+
+/// Runs every post-state of every fork of the given tests, skipping the
+/// tests that need an [`UNSUPPORTED`] feature.
+pub fn run_tests(tests: &[(String, StateTest)]) -> Report {
+  let mut report = Report::default();
+  for (name, test) in tests {
+    let unsupported = UNSUPPORTED
+      .iter()
+      .find(|(_, needs)| needs(test))
+      .map(|(feature, _)| format!("{feature} is not supported"));
+
+    for (fork, posts) in &test.post {
+      let fork_report = report.forks.entry(fork.clone()).or_default();
+      for (i, post) in posts.iter().enumerate() {
+        if let Some(reason) = &unsupported {
+          fork_report.skipped.push(Failure {
+            case: format!("{name}[{i}]"),
+            reason: reason.clone(),
+          });
+          continue;
+        }
+
+        match run_case(test, fork, post) {
+          Ok(()) => fork_report.passed += 1,
+          Err(e) => fork_report.failures.push(Failure {
+            case: format!("{name}[{i}]"),
+            reason: e.to_string(),
+          }),
+        }
+      }
+    }
+  }
+  report
+}
+
+/// Applies the transaction selected by `post` to the pre-state and
+/// compares the outcome with the expected post-state.
+pub fn run_case(test: &StateTest, fork: &str, post: &PostState) -> Result<()> {
+  let tx = &test.transaction;
+  let env = &test.env;
+  let london = is_at_least(fork, "London");
+
+  let data = tx.data.get(post.indexes.data).context("data index")?;
+  let gas_limit = tx.gas_limit.get(post.indexes.gas).context("gas index")?;
+  let value = *tx.value.get(post.indexes.value).context("value index")?;
+  let sender = match tx.sender {
+    Some(sender) => sender,
+    None => secret_key_address(&tx.secret_key)?,
+  };
+  let to = match tx.to.trim_start_matches("0x") {
+    "" => None,
+    to => Some(H160::from_slice(&hex::decode(to)?)),
+  };
+
+  let base_fee = match london {
+    true => env.current_base_fee.unwrap_or_default(),
+    false => U256::zero(),
+  };
+  let (max_fee, priority_fee) = match (tx.gas_price, tx.max_fee_per_gas) {
+    (Some(price), _) => (price, price.saturating_sub(base_fee)),
+    (None, Some(max_fee)) => {
+      let tip = tx.max_priority_fee_per_gas.unwrap_or_default();
+      (max_fee, tip.min(max_fee.saturating_sub(base_fee)))
+    }
+    (None, None) => return Err(anyhow!("transaction has no gas price")),
+  };
+  let gas_price = base_fee + priority_fee;
+
+  let mut host = InMemoryHost::new(TransactionContext {
+    tx_gas_price: gas_price,
+    tx_origin: sender,
+    block_coinbase: env.current_coinbase,
+    block_number: env.current_number.low_u64(),
+    block_timestamp: env.current_timestamp.low_u64(),
+    block_gas_limit: env.current_gas_limit.low_u64(),
+    block_difficulty: match (is_at_least(fork, "Merge"), env.current_random) {
+      (true, Some(random)) => U256::from_big_endian(random.as_bytes()),
+      _ => env.current_difficulty,
+    },
+    chain_id: CHAIN_ID.into(),
+    block_base_fee: base_fee,
+    revision: revision(fork),
+  });
+
+  // state tests define the hash of block `n` as keccak256 of `n` in decimal
+  let number = env.current_number.low_u64();
+  for block in number.saturating_sub(256)..number {
+    let hash = Keccak256::digest(block.to_string());
+    host.set_block_hash(block, U256::from_big_endian(&hash));
+  }
+
+  for (address, account) in &test.pre {
+    host.insert_account(*address, InMemoryAccount {
+      nonce: account.nonce.low_u64(),
+      balance: account.balance,
+      code: account.code.clone(),
+      storage: account.storage.iter().map(|(k, v)| (*k, *v)).collect(),
+    });
+  }
+
+  let sender_account = host.account(sender).unwrap_or_default();
+  let intrinsic = intrinsic_gas(data, to.is_none());
+  let max_cost = gas_limit
+    .checked_mul(max_fee)
+    .and_then(|cost| cost.checked_add(value));
+
+  let rejection = if U256::from(sender_account.nonce) != tx.nonce {
+    Some("nonce mismatch")
+  } else if !sender_account.code.is_empty() {
+    Some("sender is not an EOA")
+  } else if gas_limit < &U256::from(intrinsic) {
+    Some("intrinsic gas too low")
+  } else if gas_limit > &env.current_gas_limit {
+    Some("gas limit exceeds block gas limit")
+  } else if max_fee < base_fee {
+    Some("max fee per gas less than block base fee")
+  } else if tx
+    .max_priority_fee_per_gas
+    .map_or(false, |tip| tip > max_fee)
+  {
+    Some("max priority fee per gas higher than max fee per gas")
+  } else if max_cost.map_or(true, |cost| cost > sender_account.balance) {
+    Some("insufficient funds for gas * price + value")
+  } else {
+    None
+  };
+
+  let mut gas_used = 0;
+  match (rejection, &post.expect_exception) {
+    (Some(reason), None) => return Err(anyhow!("transaction rejected: {reason}")),
+    (None, Some(exception)) => {
+      return Err(anyhow!("transaction accepted, expected {exception}"))
+    }
+    (Some(_), Some(_)) => {}
+    (None, None) => {
+      let recipient = to.ok_or_else(|| anyhow!("contract creation is not supported"))?;
+      gas_used = execute(&host, TxParams {
+        sender,
+        recipient,
+        data: data.clone(),
+        value,
+        gas_limit: gas_limit.low_u64(),
+        intrinsic,
+        gas_price,
+        priority_fee,
+        coinbase: env.current_coinbase,
+      })?;
+    }
+  }
+
+  let state_root = state_root(&host.accounts());
+  let logs_hash = logs_hash(&host.logs());
+  if state_root != post.hash {
+    return Err(anyhow!(
+      "state root mismatch: expected {:?}, got {state_root:?} (gas used {gas_used})",
+      post.hash
+    ));
+  }
+  if logs_hash != post.logs {
+    return Err(anyhow!(
+      "logs hash mismatch: expected {:?}, got {logs_hash:?}",
+      post.logs
+    ));
+  }
+  Ok(())
+}
+
+struct TxParams {
+  sender: H160,
+  recipient: H160,
+  data: Bytes,
+  value: U256,
+  gas_limit: u64,
+  intrinsic: u64,
+  gas_price: U256,
+  priority_fee: U256,
+  coinbase: H160,
+}
+
+/// Executes a valid transaction and settles its fees, returns gas used.
+fn execute(host: &InMemoryHost, tx: TxParams) -> Result<u64> {
+  let mut sender = host.account(tx.sender).unwrap_or_default();
+  sender.nonce += 1;
+  sender.balance -= tx.gas_price * tx.gas_limit;
+  host.insert_account(tx.sender, sender);
+
+  let message = Message {
+    kind: CallKind::Call,
+    is_static: false,
+    depth: 0,
+    gas: (tx.gas_limit - tx.intrinsic) as i64,
+    recipient: tx.recipient,
+    sender: tx.sender,
+    input_data: tx.data,
+    value: tx.value,
+  };
+
+  let output = panic::catch_unwind(AssertUnwindSafe(|| host.call(Call::Call(&message))))
+    .map_err(|e| {
+      let reason = e
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| e.downcast_ref::<String>().cloned())
+        .unwrap_or_default();
+      anyhow!("interpreter panicked: {reason}")
+    })?;
+
+  // the refund is capped against all gas used, intrinsic gas included
+  let gas_used = tx.gas_limit - output.gas_left.max(0) as u64;
+  let gas_used = gas_used
+    - host
+      .transaction_context()
+      .revision
+      .capped_refund(output.gas_refund, gas_used);
+
+  // self-destructed balances go to their beneficiaries,
+  // unless an account destroys itself in which case they are burnt.
+  for (address, beneficiary) in host.selfdestructs() {
+    if let Some(account) = host.remove_account(address) {
+      if beneficiary != address {
+        let balance = host.get_balance(beneficiary).unwrap_or_default();
+        host.set_balance(beneficiary, balance + account.balance);
+      }
+    }
+  }
+
+  // the sender paid for the whole gas limit upfront
+  let sender_balance = host.get_balance(tx.sender).unwrap_or_default();
+  host.set_balance(
+    tx.sender,
+    sender_balance + tx.gas_price * (tx.gas_limit - gas_used),
+  );
+
+  let coinbase_balance = host.get_balance(tx.coinbase).unwrap_or_default();
+  host.set_balance(tx.coinbase, coinbase_balance + tx.priority_fee * gas_used);
+
+  // touched accounts that end up empty are removed (EIP-161)
+  for address in [tx.coinbase, tx.recipient] {
+    if host.account(address).map_or(false, |a| a.is_empty()) {
+      host.remove_account(address);
+    }
+  }
+
+  Ok(gas_used)
+}
+
+/// True if `fork` activates at or after `other`. Unknown forks are
+/// assumed to be newer than all known ones.
+pub(crate) fn is_at_least(fork: &str, other: &str) -> bool {
+  let position = |name| FORKS.iter().position(|f| *f == name);
+  match (position(fork), position(other)) {
+    (Some(a), Some(b)) => a >= b,
+    _ => true,
+  }
+}
+
+/// The revision to run `fork` under. Forks before Berlin are not
+/// supported by the interpreter and run under Berlin rules.
+pub(crate) fn revision(fork: &str) -> Revision {
+  match fork {
+    _ if is_at_least(fork, "Cancun") => Revision::Cancun,
+    _ if is_at_least(fork, "Shanghai") => Revision::Shanghai,
+    _ if is_at_least(fork, "London") => Revision::London,
+    _ => Revision::Berlin,
+  }
+}
+
+pub(crate) fn secret_key_address(secret_key: &H256) -> Result<H160> {
+  let key = SecretKey::parse_slice(secret_key.as_bytes())
+    .map_err(|e| anyhow!("invalid secret key: {e}"))?;
+  let public = PublicKey::from_secret_key(&key).serialize();
+  Ok(H160::from_slice(&Keccak256::digest(&public[1..])[12..]))
+}
+
+fn keccak(data: &[u8]) -> Vec<u8> {
+  Keccak256::digest(data).to_vec()
+}
+
+fn u256_bytes(value: U256) -> [u8; 32] {
+  let mut bytes = [0u8; 32];
+  value.to_big_endian(&mut bytes);
+  bytes
+}
+
+/// Root of the secure trie of all accounts, as found in block headers.
+pub fn state_root(accounts: &HashMap<H160, InMemoryAccount>) -> H256 {
+  trie_root(accounts.iter().map(|(address, account)| {
+    let storage_root = trie_root(
+      account
+        .storage
+        .iter()
+        .filter(|(_, v)| !v.is_zero())
+        .map(|(k, v)| (keccak(&u256_bytes(*k)), rlp::encode(v).to_vec())),
+    );
+
+    let mut stream = RlpStream::new_list(4);
+    stream.append(&account.nonce);
+    stream.append(&account.balance);
+    stream.append(&storage_root);
+    stream.append(&keccak(&account.code));
+    (keccak(address.as_bytes()), stream.out().to_vec())
+  }))
+}
+
+/// Hash of the RLP encoded list of logs.
+pub fn logs_hash(logs: &[Log]) -> H256 {
+  let mut stream = RlpStream::new_list(logs.len());
+  for log in logs {
+    stream.begin_list(3);
+    stream.append(&log.address);
+    stream.begin_list(log.topics.len());
+    for topic in &log.topics {
+      stream.append(&H256(u256_bytes(*topic)));
+    }
+    stream.append(&log.data.to_vec());
+  }
+  H256::from_slice(&Keccak256::digest(stream.out()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn empty_logs_hash() {
+    assert_eq!(
+      logs_hash(&[]),
+      H256(hex_literal::hex!(
+        "1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"
+      ))
+    );
+  }
+
+  #[test]
+  fn fork_order() {
+    assert!(is_at_least("London", "Berlin"));
+    assert!(is_at_least("London", "London"));
+    assert!(!is_at_least("Berlin", "London"));
+    assert!(is_at_least("Prague", "London"));
+  }
+
+  #[test]
+  fn creations_are_skipped() {
+    let tests = load_tests::<StateTest>(Path::new(concat!(
+      env!("CARGO_MANIFEST_DIR"),
+      "/fixtures/GeneralStateTests"
+    )))
+    .unwrap();
+    let (name, mut test) = tests.into_iter().next().unwrap();
+    test.transaction.to = String::new();
+
+    let report = run_tests(&[(name, test)]);
+    assert_eq!(report.passed() + report.failed(), 0);
+    assert!(report.skipped() > 0);
+  }
+
+  #[test]
+  fn fork_revision() {
+    assert_eq!(revision("Istanbul"), Revision::Berlin);
+    assert_eq!(revision("Berlin"), Revision::Berlin);
+    assert_eq!(revision("Paris"), Revision::London);
+    assert_eq!(revision("Shanghai"), Revision::Shanghai);
+    assert_eq!(revision("Prague"), Revision::Cancun);
+  }
+
+  /// Runs the vendored fixtures, or the ones in `STATE_TESTS_DIR`, prints
+  /// a report and fails on any case that is neither passed nor skipped.
+  #[test]
+  fn general_state_tests() {
+    let dir = std::env::var("STATE_TESTS_DIR").unwrap_or_else(|_| {
+      concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/GeneralStateTests").into()
+    });
+
+    let tests = load_tests::<StateTest>(Path::new(&dir)).unwrap();
+    assert!(!tests.is_empty(), "no fixtures found in {dir}");
+
+    let report = run_tests(&tests);
+    println!("{report}");
+
+    assert!(report.passed() > 0, "{report}");
+    assert_eq!(report.failed(), 0, "{report}");
+  }
+}
@@ -0,0 +1,143 @@
+//! Root hash of an Ethereum Merkle Patricia Trie.
+//!
+//! Only computing the root of a complete set of entries is supported,
+//! which is all that is needed to compare post-state against the state
+//! root recorded in Ethereum test fixtures.
+
+use {
+  fvm_evm::H256,
+  rlp::RlpStream,
+  sha3::{Digest, Keccak256},
+};
+
+/// Returns the root of a trie holding `entries`.
+///
+/// Keys are used as-is, callers of a secure trie are expected to hash them
+/// first. Values are stored as opaque byte strings.
+pub fn trie_root(entries: impl IntoIterator<Item = (Vec<u8>, Vec<u8>)>) -> H256 {
+  let mut items: Vec<_> = entries
+    .into_iter()
+    .map(|(key, value)| (to_nibbles(&key), value))
+    .collect();
+  items.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+  items.dedup_by(|a, b| a.0 == b.0);
+
+  // the root is always referenced by its hash, even when shorter than 32 bytes
+  H256::from_slice(&Keccak256::digest(encode_node(&items, 0)))
+}
+
+fn to_nibbles(key: &[u8]) -> Vec<u8> {
+  key.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Compact encoding of a nibble path (Appendix C of the yellow paper).
+fn hex_prefix(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+  let flag = if leaf { 2 } else { 0 };
+  let mut bytes = Vec::with_capacity(nibbles.len() / 2 + 1);
+  let rest = match nibbles.len() % 2 {
+    1 => {
+      bytes.push((flag + 1) << 4 | nibbles[0]);
+      &nibbles[1..]
+    }
+    _ => {
+      bytes.push(flag << 4);
+      nibbles
+    }
+  };
+  bytes.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+  bytes
+}
+
+/// Returns the RLP encoding of the node holding sorted `items`, whose keys
+/// share their first `depth` nibbles.
+fn encode_node(items: &[(Vec<u8>, Vec<u8>)], depth: usize) -> Vec<u8> {
+  let mut stream = RlpStream::new();
+  match items {
+    [] => {
+      stream.append_empty_data();
+    }
+    [(key, value)] => {
+      stream.begin_list(2);
+      stream.append(&hex_prefix(&key[depth..], true));
+      stream.append(value);
+    }
+    [first, .., last] => {
+      let shared = first.0[depth..]
+        .iter()
+        .zip(&last.0[depth..])
+        .take_while(|(a, b)| a == b)
+        .count();
+
+      if shared > 0 {
+        stream.begin_list(2);
+        stream.append(&hex_prefix(&first.0[depth..depth + shared], false));
+        append_child(&mut stream, encode_node(items, depth + shared));
+      } else {
+        stream.begin_list(17);
+        let (value, mut rest) = match items[0].0.len() == depth {
+          true => (Some(&items[0].1), &items[1..]),
+          false => (None, items),
+        };
+        for nibble in 0..16 {
+          let len = rest.iter().take_while(|(k, _)| k[depth] == nibble).count();
+          match len {
+            0 => {
+              stream.append_empty_data();
+            }
+            _ => append_child(&mut stream, encode_node(&rest[..len], depth + 1)),
+          }
+          rest = &rest[len..];
+        }
+        match value {
+          Some(value) => stream.append(value),
+          None => stream.append_empty_data(),
+        };
+      }
+    }
+  }
+  stream.out().to_vec()
+}
+
+/// Nodes shorter than 32 bytes are embedded in their parent, all others
+/// are referenced by their hash.
+fn append_child(stream: &mut RlpStream, node: Vec<u8>) {
+  match node.len() < 32 {
+    true => stream.append_raw(&node, 1),
+    false => stream.append(&Keccak256::digest(&node).as_slice()),
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, hex_literal::hex};
+
+  #[test]
+  fn empty_trie() {
+    assert_eq!(
+      trie_root(vec![]),
+      H256(hex!(
+        "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+      ))
+    );
+  }
+
+  #[test]
+  fn known_roots() {
+    // from the trieanyorder fixture of ethereum/tests
+    let entries = [
+      ("doe", "reindeer"),
+      ("dog", "puppy"),
+      ("dogglesworth", "cat"),
+    ];
+    assert_eq!(
+      trie_root(
+        entries
+          .iter()
+          .map(|(k, v)| (k.as_bytes().to_vec(), v.as_bytes().to_vec()))
+      ),
+      H256(hex!(
+        "8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"
+      ))
+    );
+  }
+}
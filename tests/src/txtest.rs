@@ -0,0 +1,240 @@
+//! Runner for Ethereum TransactionTests fixtures.
+//!
+//! Every fixture is a raw signed transaction together with the outcome
+//! expected for each fork: either the transaction hash, its sender and
+//! intrinsic gas, or the exception it should be rejected with. The bytes
+//! are decoded with [`SignedTransaction::try_from`] and the sender is
+//! recovered from the signature, so a transaction is accepted only if
+//! both succeed.
+//!
+//! The fixtures are in the filled JSON format of
+//! <https://github.com/ethereum/tests>. A small set is vendored under
+//! `fixtures/TransactionTests`; set `TRANSACTION_TESTS_DIR` to run a full
+//! checkout instead.
+
+use {
+  crate::statetest::{hex_bytes, is_at_least, Failure, Report},
+  anyhow::{anyhow, bail, Result},
+  bytes::Bytes,
+  fvm_evm::{SignedTransaction, Transaction, H160, H256, U256},
+  libsecp256k1::{recover, Message, RecoveryId, Signature},
+  serde::Deserialize,
+  sha3::{Digest, Keccak256},
+  std::collections::BTreeMap,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct TransactionTest {
+  pub result: BTreeMap<String, Expectation>,
+  /// Older fixtures store the transaction under `rlp`.
+  #[serde(alias = "rlp", deserialize_with = "hex_bytes")]
+  pub txbytes: Bytes,
+}
+
+/// Expected outcome for a single fork.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Expectation {
+  #[serde(default)]
+  pub hash: Option<H256>,
+  #[serde(default)]
+  pub sender: Option<H160>,
+  #[serde(default)]
+  pub intrinsic_gas: Option<U256>,
+  /// Set if the transaction is invalid in this fork.
+  #[serde(default)]
+  pub exception: Option<String>,
+}
+
+/// Properties of a transaction that passed validation.
+#[derive(Debug)]
+pub struct Accepted {
+  pub hash: H256,
+  pub sender: H160,
+  pub intrinsic_gas: u64,
+}
+
+/// Runs the expectation of every fork of the given tests.
+pub fn run_tests(tests: &[(String, TransactionTest)]) -> Report {
+  let mut report = Report::default();
+  for (name, test) in tests {
+    for (fork, expected) in &test.result {
+      let fork_report = report.forks.entry(fork.clone()).or_default();
+      match run_case(test, fork, expected) {
+        Ok(()) => fork_report.passed += 1,
+        Err(e) => fork_report.failures.push(Failure {
+          case: name.clone(),
+          reason: e.to_string(),
+        }),
+      }
+    }
+  }
+  report
+}
+
+/// Validates the transaction bytes of `test` under the rules of `fork`
+/// and compares the outcome with the expected one.
+pub fn run_case(
+  test: &TransactionTest,
+  fork: &str,
+  expected: &Expectation,
+) -> Result<()> {
+  let accepted = match (validate(&test.txbytes, fork), &expected.exception) {
+    (Ok(_), Some(exception)) => {
+      return Err(anyhow!("transaction accepted, expected {exception}"))
+    }
+    (Err(e), None) => return Err(anyhow!("transaction rejected: {e}")),
+    (Err(_), Some(_)) => return Ok(()),
+    (Ok(accepted), None) => accepted,
+  };
+
+  if let Some(hash) = expected.hash {
+    if hash != accepted.hash {
+      bail!("hash mismatch: expected {hash:?}, got {:?}", accepted.hash);
+    }
+  }
+  if let Some(sender) = expected.sender {
+    if sender != accepted.sender {
+      bail!(
+        "sender mismatch: expected {sender:?}, got {:?}",
+        accepted.sender
+      );
+    }
+  }
+  if let Some(gas) = expected.intrinsic_gas {
+    if gas != U256::from(accepted.intrinsic_gas) {
+      bail!(
+        "intrinsic gas mismatch: expected {gas}, got {}",
+        accepted.intrinsic_gas
+      );
+    }
+  }
+  Ok(())
+}
+
+/// Decodes a raw transaction and checks everything that can be checked
+/// without access to the state.
+pub fn validate(bytes: &[u8], fork: &str) -> Result<Accepted> {
+  let tx =
+    SignedTransaction::try_from(bytes).map_err(|e| anyhow!("decoding failed: {e}"))?;
+
+  match tx.transaction {
+    Transaction::EIP2930 { .. } if !is_at_least(fork, "Berlin") => {
+      bail!("EIP-2930 transactions are not supported before Berlin")
+    }
+    Transaction::EIP1559 { .. } if !is_at_least(fork, "London") => {
+      bail!("EIP-1559 transactions are not supported before London")
+    }
+    _ => {}
+  }
+
+  let sender = recover_sender(&tx)?;
+  let intrinsic_gas = tx.intrinsic_gas();
+  if tx.gas_limit() < intrinsic_gas {
+    bail!(
+      "intrinsic gas too low: gas limit {}, intrinsic gas {intrinsic_gas}",
+      tx.gas_limit()
+    );
+  }
+
+  Ok(Accepted {
+    hash: tx.hash(),
+    sender,
+    intrinsic_gas,
+  })
+}
+
+/// Recovers the sender address from the transaction signature.
+///
+/// [`SignedTransaction::sender_address`] goes through an FVM syscall,
+/// this does the same recovery natively with the library backing that
+/// syscall. The signature values are taken as they are, their ranges are
+/// checked by the decoder.
+fn recover_sender(tx: &SignedTransaction) -> Result<H160> {
+  let recovery_id = match tx.transaction {
+    Transaction::Legacy { .. } => tx.signature.v.odd_y_parity(),
+    _ => tx.signature.v.0 as u8,
+  };
+
+  let mut signature = [0u8; 64];
+  signature[..32].copy_from_slice(tx.signature.r.as_bytes());
+  signature[32..].copy_from_slice(tx.signature.s.as_bytes());
+
+  let signature = Signature::parse_overflowing(&signature);
+  let recovery_id =
+    RecoveryId::parse(recovery_id).map_err(|e| anyhow!("invalid v value: {e:?}"))?;
+  let public = recover(
+    &Message::parse(&tx.transaction.hash().to_fixed_bytes()),
+    &signature,
+    &recovery_id,
+  )
+  .map_err(|e| anyhow!("failed to recover sender: {e:?}"))?;
+
+  Ok(H160::from_slice(
+    &Keccak256::digest(&public.serialize()[1..])[12..],
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::{sign_evm_transaction, statetest::load_tests},
+    fvm_evm::TransactionAction,
+    libsecp256k1::SecretKey,
+    std::path::Path,
+  };
+
+  #[test]
+  fn recovers_sender() {
+    let seckey = SecretKey::parse(&hex_literal::hex!(
+      "45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8"
+    ))
+    .unwrap();
+    let tx = sign_evm_transaction(
+      Transaction::EIP1559 {
+        chain_id: 1,
+        nonce: 3,
+        max_priority_fee_per_gas: 1.into(),
+        max_fee_per_gas: 10.into(),
+        gas_limit: 21000,
+        action: TransactionAction::Call(H160::repeat_byte(0x11)),
+        value: 5.into(),
+        input: Bytes::new(),
+        access_list: vec![],
+      },
+      seckey,
+    );
+
+    let accepted = validate(&tx.serialize(), "London").unwrap();
+    assert_eq!(
+      accepted.sender,
+      H160(hex_literal::hex!(
+        "a94f5374fce5edbc8e2a8697c15331677e6ebf0b"
+      ))
+    );
+    assert_eq!(accepted.hash, tx.hash());
+    assert_eq!(accepted.intrinsic_gas, 21000);
+
+    // dynamic fee transactions only exist since London
+    assert!(validate(&tx.serialize(), "Berlin").is_err());
+  }
+
+  /// Runs the vendored fixtures, or the ones in `TRANSACTION_TESTS_DIR`,
+  /// and fails on any mismatch.
+  #[test]
+  fn transaction_tests() {
+    let dir = std::env::var("TRANSACTION_TESTS_DIR").unwrap_or_else(|_| {
+      concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/TransactionTests").into()
+    });
+
+    let tests = load_tests::<TransactionTest>(Path::new(&dir)).unwrap();
+    assert!(!tests.is_empty(), "no fixtures found in {dir}");
+
+    let report = run_tests(&tests);
+    println!("{report}");
+
+    assert!(report.passed() > 0);
+    assert_eq!(report.failed(), 0, "{report}");
+  }
+}